@@ -0,0 +1,117 @@
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::path::Path;
+
+use crate::{WadDirectoryEntry, WadHeader, WadString, WadType};
+
+/// Builds a new WAD file from lumps added in order.
+///
+/// Lump data is written straight after the header, in the order the lumps were
+/// added, followed by the directory.
+#[derive(Clone, Debug)]
+pub struct WadBuilder {
+	wad_type: WadType,
+	lumps: Vec<(WadString, Vec<u8>)>,
+}
+
+impl WadBuilder {
+	pub fn new(wad_type: WadType) -> Self {
+		WadBuilder {
+			wad_type,
+			lumps: Vec::new(),
+		}
+	}
+
+	/// Add a lump to the end of the WAD
+	pub fn add_lump(&mut self, name: WadString, data: impl Into<Vec<u8>>) -> &mut Self {
+		self.lumps.push((name, data.into()));
+		self
+	}
+
+	/// Add a virtual (zero-sized) lump, such as a `S_START` marker or a map's
+	/// header lump
+	pub fn add_marker(&mut self, name: WadString) -> &mut Self {
+		self.add_lump(name, Vec::new())
+	}
+
+	/// Write the WAD
+	pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+		let too_large = || io::Error::new(io::ErrorKind::InvalidInput, "WAD is too large");
+
+		let mut directory = Vec::with_capacity(self.lumps.len());
+		let mut offset_bytes = WadHeader::SIZE_BYTES;
+		for (name, data) in &self.lumps {
+			directory.push(WadDirectoryEntry {
+				offset_bytes: i32::try_from(offset_bytes).map_err(|_| too_large())?,
+				size_bytes: i32::try_from(data.len()).map_err(|_| too_large())?,
+				lump_name: *name,
+			});
+			offset_bytes += data.len();
+		}
+
+		let header = WadHeader {
+			iwad_or_pwad: self.wad_type,
+			num_lumps: i32::try_from(directory.len()).map_err(|_| too_large())?,
+			directory_offset_bytes: i32::try_from(offset_bytes).map_err(|_| too_large())?,
+		};
+
+		writer.write_all(&header.to_bytes())?;
+		for (_, data) in &self.lumps {
+			writer.write_all(data)?;
+		}
+		for entry in &directory {
+			writer.write_all(&entry.to_bytes())?;
+		}
+
+		writer.flush()
+	}
+
+	/// Write the WAD to a new `Vec`. Fails if the WAD is too large for the
+	/// format's 32-bit offsets.
+	pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+		let mut bytes = Vec::new();
+		self.write_to(&mut bytes)?;
+		Ok(bytes)
+	}
+
+	/// Write the WAD to a file at `path`, replacing it if it exists
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+		self.write_to(io::BufWriter::new(File::create(path)?))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Wad;
+
+	#[test]
+	fn wad_builder_round_trip() {
+		let mut builder = WadBuilder::new(WadType::PWAD);
+		builder
+			.add_lump(
+				WadString::new(*b"DEHACKED").unwrap(),
+				b"Patch File".to_vec(),
+			)
+			.add_marker(WadString::new(*b"MAP01\0\0\0").unwrap())
+			.add_lump(WadString::new(*b"THINGS\0\0").unwrap(), [0u8; 10]);
+
+		let bytes = builder.to_bytes().unwrap();
+		assert_eq!(bytes.len(), 12 + 10 + 10 + 3 * 16);
+
+		let wad = Wad::from_bytes(bytes).unwrap();
+		assert_eq!(wad.header.iwad_or_pwad, WadType::PWAD);
+
+		let names = wad
+			.directory
+			.iter()
+			.map(|entry| entry.lump_name.to_string())
+			.collect::<Vec<_>>();
+		assert_eq!(names, ["DEHACKED", "MAP01", "THINGS"]);
+
+		assert_eq!(wad.read_lump(&wad.directory[0]).unwrap(), b"Patch File");
+		assert!(wad.directory[1].is_virtual());
+		assert_eq!(wad.read_lump(&wad.directory[2]).unwrap(), [0u8; 10]);
+	}
+}
@@ -0,0 +1,91 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+/// A least-recently-used cache of lump data, keyed by directory index and
+/// bounded by the total size of the cached lumps.
+#[derive(Debug, Default)]
+pub(crate) struct LumpCache {
+	budget_bytes: usize,
+	used_bytes: usize,
+	/// Ordered from least to most recently used
+	lumps: VecDeque<(usize, Arc<[u8]>)>,
+}
+
+impl LumpCache {
+	pub fn new(budget_bytes: usize) -> Self {
+		LumpCache {
+			budget_bytes,
+			..Self::default()
+		}
+	}
+
+	pub fn get(&mut self, index: usize) -> Option<Arc<[u8]>> {
+		let position = self.lumps.iter().position(|(i, _)| *i == index)?;
+		let lump = self.lumps.remove(position).unwrap();
+		let data = lump.1.clone();
+		self.lumps.push_back(lump);
+		Some(data)
+	}
+
+	pub fn insert(&mut self, index: usize, data: Arc<[u8]>) {
+		// Another reader may have inserted the same lump since it was missed
+		self.remove(index);
+
+		if data.len() > self.budget_bytes {
+			return;
+		}
+
+		while self.used_bytes + data.len() > self.budget_bytes {
+			let (_, evicted) = self.lumps.pop_front().unwrap();
+			self.used_bytes -= evicted.len();
+		}
+
+		self.used_bytes += data.len();
+		self.lumps.push_back((index, data));
+	}
+
+	pub fn remove(&mut self, index: usize) {
+		if let Some(position) = self.lumps.iter().position(|(i, _)| *i == index) {
+			let (_, removed) = self.lumps.remove(position).unwrap();
+			self.used_bytes -= removed.len();
+		}
+	}
+
+	pub fn used_bytes(&self) -> usize {
+		self.used_bytes
+	}
+
+	pub fn clear(&mut self) {
+		self.lumps.clear();
+		self.used_bytes = 0;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn lump_cache_evicts_least_recently_used() {
+		let mut cache = LumpCache::new(10);
+		cache.insert(0, Arc::from([0u8; 4]));
+		cache.insert(1, Arc::from([1u8; 4]));
+		assert!(cache.get(0).is_some());
+
+		cache.insert(2, Arc::from([2u8; 4]));
+		assert_eq!(cache.used_bytes(), 8);
+		assert!(cache.get(0).is_some());
+		assert!(cache.get(1).is_none());
+		assert!(cache.get(2).is_some());
+
+		// Too big to ever fit, so not cached
+		cache.insert(3, Arc::from([3u8; 11]));
+		assert!(cache.get(3).is_none());
+		assert_eq!(cache.used_bytes(), 8);
+
+		// Inserting an index again replaces it instead of duplicating it
+		cache.insert(2, Arc::from([2u8; 4]));
+		assert_eq!(cache.used_bytes(), 8);
+		assert_eq!(cache.lumps.len(), 2);
+	}
+}
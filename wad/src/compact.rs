@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::io;
+use std::io::prelude::*;
+use std::ops::Range;
+
+use crate::{Wad, WadDirectoryEntry, WadHeader, WadSource};
+
+impl<S: WadSource> Wad<S> {
+	/// Byte ranges of the file that aren't part of the header, the directory,
+	/// or any lump, in order. These are left behind by editing tools (see
+	/// [`crate::WadEditor`]) and can be removed with [`Wad::compact_to`].
+	pub fn unreferenced_regions(&self) -> io::Result<Vec<Range<u64>>> {
+		let directory_start = self.header.directory_offset_bytes as u64;
+		let directory_size = (self.directory.len() * WadDirectoryEntry::SIZE_BYTES) as u64;
+
+		let mut used = self
+			.directory
+			.iter()
+			.filter(|entry| !entry.is_virtual())
+			.map(lump_range)
+			.collect::<io::Result<Vec<_>>>()?;
+		used.push(0..WadHeader::SIZE_BYTES as u64);
+		used.push(directory_start..directory_start + directory_size);
+		used.sort_by_key(|range| range.start);
+
+		let mut unused = Vec::new();
+		let mut position = 0;
+		for range in used {
+			if range.start > position {
+				unused.push(position..range.start);
+			}
+			position = position.max(range.end);
+		}
+
+		let size_bytes = self.source.size_bytes()?;
+		if size_bytes > position {
+			unused.push(position..size_bytes);
+		}
+
+		Ok(unused)
+	}
+
+	/// Write a copy of the WAD without any unreferenced regions. Lumps are
+	/// written in directory order, and lumps that share data keep sharing it.
+	///
+	/// Lump data is streamed from the source one lump at a time, so only a
+	/// small buffer is needed however large the WAD is.
+	pub fn compact_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+		let too_large = || io::Error::new(io::ErrorKind::InvalidInput, "WAD is too large");
+
+		// Work out the new layout from the directory alone. Maps the (offset,
+		// size) of the lumps' original data to its new offset.
+		let mut new_offsets = HashMap::new();
+		let mut directory = Vec::with_capacity(self.directory.len());
+		let mut lumps = Vec::new();
+		let mut offset_bytes = WadHeader::SIZE_BYTES as u64;
+
+		for entry in &self.directory {
+			let mut new_entry = entry.clone();
+
+			if entry.is_virtual() {
+				new_entry.offset_bytes = 0;
+			} else {
+				let key = (entry.offset_bytes, entry.size_bytes);
+				if let Some(offset) = new_offsets.get(&key) {
+					new_entry.offset_bytes = *offset;
+				} else {
+					let range = lump_range(entry)?;
+					let offset = i32::try_from(offset_bytes).map_err(|_| too_large())?;
+					offset_bytes += range.end - range.start;
+					new_offsets.insert(key, offset);
+					new_entry.offset_bytes = offset;
+					lumps.push(entry);
+				}
+			}
+
+			directory.push(new_entry);
+		}
+
+		let header = WadHeader {
+			iwad_or_pwad: self.header.iwad_or_pwad,
+			num_lumps: i32::try_from(directory.len()).map_err(|_| too_large())?,
+			directory_offset_bytes: i32::try_from(offset_bytes).map_err(|_| too_large())?,
+		};
+
+		writer.write_all(&header.to_bytes())?;
+		for entry in lumps {
+			io::copy(&mut self.lump_reader(entry)?, &mut writer)?;
+		}
+		for entry in &directory {
+			writer.write_all(&entry.to_bytes())?;
+		}
+
+		writer.flush()
+	}
+}
+
+fn lump_range(entry: &WadDirectoryEntry) -> io::Result<Range<u64>> {
+	let (start, size) = entry.extent()?;
+	Ok(start..start + size)
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{Wad, WadDirectoryEntry, WadHeader, WadString};
+
+	#[test]
+	fn compact_removes_unreferenced_regions() {
+		let mut bytes = Vec::new();
+		bytes.extend_from_slice(b"PWAD\x03\0\0\0\x1E\0\0\0");
+		bytes.extend_from_slice(b"junk"); // 12..16, unreferenced
+		bytes.extend_from_slice(b"GOODLUMP"); // 16..24
+		bytes.extend_from_slice(b"stale!"); // 24..30, unreferenced
+		for (offset, size, name) in [
+			(16, 8, b"LUMPA\0\0\0"),
+			(16, 8, b"LUMPB\0\0\0"),
+			(0, 0, b"MAP01\0\0\0"),
+		] {
+			let entry = WadDirectoryEntry {
+				offset_bytes: offset,
+				size_bytes: size,
+				lump_name: WadString::new(*name).unwrap(),
+			};
+			bytes.extend_from_slice(&entry.to_bytes());
+		}
+		bytes.extend_from_slice(b"trailing");
+
+		let wad = Wad::from_bytes(&bytes[..]).unwrap();
+		assert_eq!(
+			wad.unreferenced_regions().unwrap(),
+			[12..16, 24..30, 78..86]
+		);
+
+		let mut compacted = Vec::new();
+		wad.compact_to(&mut compacted).unwrap();
+		assert_eq!(compacted.len(), WadHeader::SIZE_BYTES + 8 + 3 * 16);
+
+		let compacted = Wad::from_bytes(compacted).unwrap();
+		assert!(compacted.unreferenced_regions().unwrap().is_empty());
+		assert_eq!(
+			compacted.directory[0].offset_bytes,
+			compacted.directory[1].offset_bytes
+		);
+		assert_eq!(
+			compacted.read_lump(&compacted.directory[1]).unwrap(),
+			b"GOODLUMP"
+		);
+		assert!(compacted.directory[2].is_virtual());
+	}
+}
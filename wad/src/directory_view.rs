@@ -0,0 +1,205 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use crate::{WadDirectoryEntry, WadError, WadHeader, WadSource};
+
+/// A view of a WAD's directory that reads entries from the file on demand,
+/// instead of materializing the whole directory up front like [`crate::Wad`].
+///
+/// Entries are read a page at a time and only the most recently used pages are
+/// kept, so memory use stays bounded no matter how many lumps the WAD has.
+#[derive(Debug)]
+pub struct DirectoryView<S = File> {
+	source: S,
+	pub header: WadHeader,
+	pages: Mutex<VecDeque<(usize, Arc<[WadDirectoryEntry]>)>>,
+}
+
+impl<S: WadSource> DirectoryView<S> {
+	/// Number of directory entries read from the file at once
+	pub const PAGE_ENTRIES: usize = 64;
+	/// Number of pages kept in memory
+	pub const CACHED_PAGES: usize = 16;
+
+	pub fn new(source: S) -> Result<Self, WadError> {
+		let mut header_buf = [0; WadHeader::SIZE_BYTES];
+		source
+			.read_exact_at(&mut header_buf, 0)
+			.map_err(|err| WadError::from_io(err, WadError::TruncatedHeader))?;
+
+		let header = WadHeader::new(header_buf)?;
+
+		let num_lumps = usize::try_from(header.num_lumps)
+			.map_err(|_| WadError::InvalidLumpCount(header.num_lumps))?;
+
+		// Check the whole directory is within the source up front, so reading a
+		// page later can't go out of bounds
+		let directory_size = num_lumps * WadDirectoryEntry::SIZE_BYTES;
+		let directory_end = u64::try_from(header.directory_offset_bytes)
+			.ok()
+			.and_then(|offset| offset.checked_add(directory_size as u64));
+		if directory_end.is_none_or(|end| end > source.size_bytes().unwrap_or(u64::MAX)) {
+			return Err(WadError::TruncatedDirectory);
+		}
+
+		Ok(DirectoryView {
+			source,
+			header,
+			pages: Mutex::new(VecDeque::with_capacity(Self::CACHED_PAGES)),
+		})
+	}
+
+	/// Number of entries in the directory
+	pub fn len(&self) -> usize {
+		self.header.num_lumps.max(0) as usize
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Get the directory entry at `index`, or `None` if it is out of range.
+	pub fn get(&self, index: usize) -> Result<Option<WadDirectoryEntry>, WadError> {
+		if index >= self.len() {
+			return Ok(None);
+		}
+
+		let page = self.page(index / Self::PAGE_ENTRIES)?;
+		Ok(Some(page[index % Self::PAGE_ENTRIES].clone()))
+	}
+
+	/// Iterate over the directory entries in order, reading them as needed.
+	pub fn iter(&self) -> impl Iterator<Item = Result<WadDirectoryEntry, WadError>> + '_ {
+		(0..self.len()).map(|index| self.get(index).map(Option::unwrap))
+	}
+
+	/// Read the contents of a lump into a buffer. The buffer's size must equal
+	/// the entry's `size_bytes`, or an `InvalidInput` error is returned.
+	pub fn read_lump(&self, entry: &WadDirectoryEntry, buf: &mut [u8]) -> io::Result<()> {
+		entry.read_lump_from(buf, &self.source)
+	}
+
+	fn page(&self, page_index: usize) -> Result<Arc<[WadDirectoryEntry]>, WadError> {
+		// The pages are never left inconsistent by a panic, so poisoning is ignored
+		let mut pages = self
+			.pages
+			.lock()
+			.unwrap_or_else(|poisoned| poisoned.into_inner());
+
+		if let Some(position) = pages.iter().position(|(i, _)| *i == page_index) {
+			// Move to the back so the least recently used page is evicted first
+			let page = pages.remove(position).unwrap();
+			pages.push_back(page.clone());
+			return Ok(page.1);
+		}
+
+		let first = page_index * Self::PAGE_ENTRIES;
+		let count = Self::PAGE_ENTRIES.min(self.len() - first);
+
+		let mut buf = vec![0; count * WadDirectoryEntry::SIZE_BYTES];
+		let offset = self.header.directory_offset_bytes as u64
+			+ (first * WadDirectoryEntry::SIZE_BYTES) as u64;
+
+		self.source
+			.read_exact_at(&mut buf, offset)
+			.map_err(|err| WadError::from_io(err, WadError::TruncatedDirectory))?;
+
+		let page: Arc<[WadDirectoryEntry]> = buf
+			.chunks(WadDirectoryEntry::SIZE_BYTES)
+			.map(|chunk| WadDirectoryEntry::new(chunk.try_into().unwrap()))
+			.collect::<Result<_, _>>()?;
+
+		if pages.len() == Self::CACHED_PAGES {
+			pages.pop_front();
+		}
+		pages.push_back((page_index, page.clone()));
+
+		Ok(page)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_util::temp_file;
+	use crate::MemorySource;
+
+	fn test_wad_file(num_lumps: usize) -> File {
+		let mut bytes = Vec::new();
+		bytes.extend_from_slice(b"PWAD");
+		bytes.extend_from_slice(&(num_lumps as i32).to_le_bytes());
+		bytes.extend_from_slice(&12i32.to_le_bytes());
+		for i in 0..num_lumps {
+			bytes.extend_from_slice(&(i as i32).to_le_bytes());
+			bytes.extend_from_slice(&0i32.to_le_bytes());
+			bytes.extend_from_slice(format!("LUMP{:04}", i).as_bytes());
+		}
+
+		temp_file(&bytes)
+	}
+
+	#[test]
+	fn directory_view_reads_entries_on_demand() {
+		let view = DirectoryView::new(test_wad_file(200)).unwrap();
+		assert_eq!(view.len(), 200);
+
+		let entry = view.get(130).unwrap().unwrap();
+		assert_eq!(entry.offset_bytes, 130);
+		assert_eq!(format!("{}", entry.lump_name), "LUMP0130");
+
+		assert!(view.get(200).unwrap().is_none());
+	}
+
+	#[test]
+	fn directory_view_is_thread_safe() {
+		fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+		let view = DirectoryView::new(test_wad_file(200)).unwrap();
+		assert_send_sync(&view);
+
+		std::thread::scope(|scope| {
+			for start in [0, 50, 100, 150] {
+				let view = &view;
+				scope.spawn(move || {
+					for index in start..start + 50 {
+						let entry = view.get(index).unwrap().unwrap();
+						assert_eq!(entry.lump_name.to_string(), format!("LUMP{:04}", index));
+					}
+				});
+			}
+		});
+	}
+
+	#[test]
+	fn directory_view_rejects_invalid_header() {
+		let result = DirectoryView::new(MemorySource::new(b"PWAD\xFF\xFF\xFF\xFF\x0C\0\0\0"));
+		assert!(matches!(result, Err(WadError::InvalidLumpCount(-1))));
+
+		let result = DirectoryView::new(MemorySource::new(b"PWAD\x64\0\0\0\xFF\xFF\xFF\xFF"));
+		assert!(matches!(result, Err(WadError::TruncatedDirectory)));
+
+		let result = DirectoryView::new(MemorySource::new(b"PWAD\x01\0\0\0\x0C\0\0\0"));
+		assert!(matches!(result, Err(WadError::TruncatedDirectory)));
+	}
+
+	#[test]
+	fn directory_view_keeps_a_bounded_number_of_pages() {
+		let num_lumps =
+			DirectoryView::<File>::PAGE_ENTRIES * (DirectoryView::<File>::CACHED_PAGES + 4);
+		let view = DirectoryView::new(test_wad_file(num_lumps)).unwrap();
+
+		let names = view
+			.iter()
+			.map(|entry| entry.unwrap().lump_name.to_string())
+			.collect::<Vec<_>>();
+
+		assert_eq!(names.len(), num_lumps);
+		assert_eq!(names[num_lumps - 1], format!("LUMP{:04}", num_lumps - 1));
+		assert_eq!(
+			view.pages.lock().unwrap().len(),
+			DirectoryView::<File>::CACHED_PAGES
+		);
+	}
+}
@@ -0,0 +1,217 @@
+use std::fs::File;
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::{source, Wad, WadDirectoryEntry, WadError, WadHeader, WadString};
+
+/// Edits a WAD file in place. Added and replaced lump data is appended to the
+/// end of the file, and lumps that aren't touched keep their bytes exactly as
+/// they were. The new directory is written by [`WadEditor::commit`]; until then
+/// the file still reads as the original WAD.
+///
+/// The space used by replaced and deleted lumps isn't reclaimed; it is listed
+/// by [`WadEditor::freed_regions`]. The old directory is left in place too.
+#[derive(Debug)]
+pub struct WadEditor {
+	wad: Wad<File>,
+	end_bytes: u64,
+	freed_regions: Vec<Range<u64>>,
+}
+
+impl WadEditor {
+	/// Open the WAD file at `path` for reading and writing, and start editing it
+	pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, WadError> {
+		let wad = Wad::options().writable(true).open(path)?;
+		Ok(Self::new(wad)?)
+	}
+
+	/// Start editing a WAD. Its file must be open for writing (see
+	/// [`crate::WadOptions::writable`]).
+	pub fn new(wad: Wad<File>) -> io::Result<Self> {
+		let end_bytes = wad.source.metadata()?.len();
+
+		Ok(WadEditor {
+			wad,
+			end_bytes,
+			freed_regions: Vec::new(),
+		})
+	}
+
+	/// The directory as it will be written on commit
+	pub fn directory(&self) -> &[WadDirectoryEntry] {
+		&self.wad.directory
+	}
+
+	/// Byte ranges of the file that are no longer used by any lump
+	pub fn freed_regions(&self) -> &[Range<u64>] {
+		&self.freed_regions
+	}
+
+	/// Add a lump to the end of the directory, returning its index
+	pub fn add_lump(&mut self, name: WadString, data: &[u8]) -> io::Result<usize> {
+		let index = self.wad.directory.len();
+		self.insert_lump(index, name, data)?;
+		Ok(index)
+	}
+
+	/// Insert a lump into the directory at `index`, shifting later lumps along.
+	/// Useful for adding lumps inside a namespace or map.
+	pub fn insert_lump(&mut self, index: usize, name: WadString, data: &[u8]) -> io::Result<()> {
+		if index > self.wad.directory.len() {
+			return Err(out_of_range());
+		}
+
+		let entry = self.append_data(name, data)?;
+		self.wad.directory.insert(index, entry);
+		Ok(())
+	}
+
+	/// Replace the data of the lump at `index`
+	pub fn replace_lump(&mut self, index: usize, data: &[u8]) -> io::Result<()> {
+		let name = self.entry(index)?.lump_name;
+		let entry = self.append_data(name, data)?;
+		let old_entry = std::mem::replace(&mut self.wad.directory[index], entry);
+		self.free(&old_entry);
+		Ok(())
+	}
+
+	/// Rename the lump at `index`
+	pub fn rename_lump(&mut self, index: usize, name: WadString) -> io::Result<()> {
+		self.entry(index)?;
+		self.wad.directory[index].lump_name = name;
+		Ok(())
+	}
+
+	/// Remove the lump at `index` from the directory
+	pub fn delete_lump(&mut self, index: usize) -> io::Result<WadDirectoryEntry> {
+		self.entry(index)?;
+		let old_entry = self.wad.directory.remove(index);
+		self.free(&old_entry);
+		Ok(old_entry)
+	}
+
+	/// Write the new directory and header, and return the edited WAD
+	pub fn commit(mut self) -> io::Result<Wad<File>> {
+		let num_lumps = i32::try_from(self.wad.directory.len()).map_err(|_| too_large())?;
+		let directory_offset_bytes = i32::try_from(self.end_bytes).map_err(|_| too_large())?;
+
+		let directory = self
+			.wad
+			.directory
+			.iter()
+			.flat_map(|entry| entry.to_bytes())
+			.collect::<Vec<_>>();
+		source::write_all_at(&self.wad.source, &directory, self.end_bytes)?;
+
+		let header = WadHeader {
+			iwad_or_pwad: self.wad.header.iwad_or_pwad,
+			num_lumps,
+			directory_offset_bytes,
+		};
+		source::write_all_at(&self.wad.source, &header.to_bytes(), 0)?;
+
+		self.wad.header = header;
+		self.wad.clear_cache();
+		self.wad.rebuild_index();
+		Ok(self.wad)
+	}
+
+	fn entry(&self, index: usize) -> io::Result<&WadDirectoryEntry> {
+		self.wad.directory.get(index).ok_or_else(out_of_range)
+	}
+
+	fn append_data(&mut self, name: WadString, data: &[u8]) -> io::Result<WadDirectoryEntry> {
+		let entry = WadDirectoryEntry {
+			offset_bytes: i32::try_from(self.end_bytes).map_err(|_| too_large())?,
+			size_bytes: i32::try_from(data.len()).map_err(|_| too_large())?,
+			lump_name: name,
+		};
+
+		source::write_all_at(&self.wad.source, data, self.end_bytes)?;
+		self.end_bytes += data.len() as u64;
+
+		Ok(entry)
+	}
+
+	fn free(&mut self, old_entry: &WadDirectoryEntry) {
+		if old_entry.is_virtual() {
+			return;
+		}
+		// An entry with a negative offset or size doesn't point at any data
+		let (Ok(start), Ok(size_bytes)) = (
+			u64::try_from(old_entry.offset_bytes),
+			u64::try_from(old_entry.size_bytes),
+		) else {
+			return;
+		};
+
+		// Lumps can share data, so only free it if nothing else uses it
+		let still_used = self.wad.directory.iter().any(|entry| {
+			entry.offset_bytes == old_entry.offset_bytes && entry.size_bytes == old_entry.size_bytes
+		});
+		if !still_used {
+			self.freed_regions.push(start..start + size_bytes);
+		}
+	}
+}
+
+fn too_large() -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidInput, "WAD is too large")
+}
+
+fn out_of_range() -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidInput, "lump index is out of range")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_util::{name, temp_path};
+	use crate::{WadBuilder, WadType};
+
+	#[test]
+	fn wad_editor_add_replace_delete() {
+		let mut builder = WadBuilder::new(WadType::PWAD);
+		builder
+			.add_lump(name("PLAYPAL"), b"palette".to_vec())
+			.add_lump(name("COLORMAP"), b"colormap".to_vec())
+			.add_lump(name("ENDOOM"), b"endoom".to_vec());
+		let path = temp_path(&builder.to_bytes().unwrap());
+		let playpal = Wad::open(&path).unwrap().directory[0].clone();
+
+		let mut editor = WadEditor::open(&path).unwrap();
+		editor.replace_lump(1, b"new colormap").unwrap();
+		editor.delete_lump(2).unwrap();
+		editor
+			.insert_lump(0, name("DEHACKED"), b"Patch File")
+			.unwrap();
+		editor.rename_lump(2, name("COLORMP2")).unwrap();
+		assert_eq!(editor.freed_regions(), [19..27, 27..33]);
+
+		let end_bytes = editor.end_bytes;
+		editor.replace_lump(3, b"data").unwrap_err();
+		editor.rename_lump(3, name("DEMO1")).unwrap_err();
+		editor.delete_lump(3).unwrap_err();
+		let err = editor.insert_lump(4, name("DEMO1"), b"data").unwrap_err();
+		assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+		assert_eq!(editor.end_bytes, end_bytes);
+
+		editor.commit().unwrap();
+		let wad = Wad::open(&path).unwrap();
+		std::fs::remove_file(&path).ok();
+
+		let names = wad
+			.directory
+			.iter()
+			.map(|entry| entry.lump_name.to_string())
+			.collect::<Vec<_>>();
+		assert_eq!(names, ["DEHACKED", "PLAYPAL", "COLORMP2"]);
+
+		// Untouched lumps stay where they were
+		assert_eq!(wad.directory[1].offset_bytes, playpal.offset_bytes);
+		assert_eq!(wad.read_lump(&wad.directory[1]).unwrap(), b"palette");
+		assert_eq!(wad.read_lump(&wad.directory[2]).unwrap(), b"new colormap");
+		assert_eq!(wad.read_lump(&wad.directory[0]).unwrap(), b"Patch File");
+	}
+}
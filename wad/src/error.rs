@@ -0,0 +1,123 @@
+use std::fmt::Display;
+use std::io;
+
+/// The ways that loading a WAD can fail
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum WadError {
+	/// An I/O error other than running out of data
+	Io(io::Error),
+	/// The file ended before the end of the header
+	TruncatedHeader,
+	/// The file ended before the end of the directory
+	TruncatedDirectory,
+	/// The identification at the start of the header was not `IWAD` or `PWAD`
+	InvalidWadType([u8; 4]),
+	/// The header's lump count is negative
+	InvalidLumpCount(i32),
+	/// A lump name contains non-ASCII characters
+	InvalidLumpName([u8; 8]),
+	/// A lump name is longer than 8 characters
+	LumpNameTooLong(String),
+	/// A lump name is empty
+	EmptyLumpName,
+	/// A lump has a negative offset or size
+	InvalidLumpExtent {
+		index: usize,
+		offset_bytes: i32,
+		size_bytes: i32,
+	},
+	/// The directory has more entries than allowed by the loading limits
+	TooManyLumps { num_lumps: usize, max: usize },
+	/// A lump is larger than allowed by the loading limits
+	LumpTooLarge {
+		index: usize,
+		size_bytes: usize,
+		max: usize,
+	},
+	/// The lumps' total size is larger than allowed by the loading limits
+	LumpsTooLarge { total_bytes: u64, max: u64 },
+	/// A metadata lump couldn't be parsed; `line` is 1-based
+	InvalidMetadata { line: usize },
+	/// A metadata key can't be written, because it isn't a bare TOML key or
+	/// clashes with one of the standard keys
+	InvalidMetadataKey(String),
+}
+
+impl WadError {
+	/// Convert an I/O error, treating running out of data as `truncated`
+	pub(crate) fn from_io(err: io::Error, truncated: Self) -> Self {
+		match err.kind() {
+			io::ErrorKind::UnexpectedEof => truncated,
+			_ => Self::Io(err),
+		}
+	}
+}
+
+impl Display for WadError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Io(err) => write!(f, "I/O error: {}", err),
+			Self::TruncatedHeader => write!(f, "file ended before the end of the WAD header"),
+			Self::TruncatedDirectory => write!(f, "file ended before the end of the WAD directory"),
+			Self::InvalidWadType(bytes) => {
+				write!(f, "invalid WAD type {:?}", String::from_utf8_lossy(bytes))
+			}
+			Self::InvalidLumpCount(count) => write!(f, "invalid lump count {}", count),
+			Self::InvalidLumpName(bytes) => write!(
+				f,
+				"invalid (non-ASCII) lump name {}",
+				crate::WadString::new_lossy(*bytes)
+			),
+			Self::LumpNameTooLong(name) => write!(f, "lump name {:?} is too long", name),
+			Self::EmptyLumpName => write!(f, "lump name is empty"),
+			Self::InvalidLumpExtent {
+				index,
+				offset_bytes,
+				size_bytes,
+			} => write!(
+				f,
+				"lump {} has an invalid offset ({}) or size ({})",
+				index, offset_bytes, size_bytes
+			),
+			Self::TooManyLumps { num_lumps, max } => {
+				write!(
+					f,
+					"directory has {} lumps, more than the limit of {}",
+					num_lumps, max
+				)
+			}
+			Self::LumpTooLarge {
+				index,
+				size_bytes,
+				max,
+			} => write!(
+				f,
+				"lump {} is {} bytes, more than the limit of {}",
+				index, size_bytes, max
+			),
+			Self::LumpsTooLarge { total_bytes, max } => write!(
+				f,
+				"lumps total {} bytes, more than the limit of {}",
+				total_bytes, max
+			),
+			Self::InvalidMetadata { line } => write!(f, "invalid metadata on line {}", line),
+			Self::InvalidMetadataKey(key) => write!(f, "invalid metadata key {:?}", key),
+		}
+	}
+}
+
+impl std::error::Error for WadError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::Io(err) => Some(err),
+			_ => None,
+		}
+	}
+}
+
+impl From<io::Error> for WadError {
+	fn from(err: io::Error) -> Self {
+		Self::Io(err)
+	}
+}
@@ -1,43 +1,428 @@
-use std::any::type_name;
 use std::fmt::Display;
 use std::fs::File;
 use std::io::prelude::*;
-use std::io::SeekFrom;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+mod builder;
+mod cache;
+mod compact;
+mod directory_view;
+mod editor;
+mod error;
+mod lump_reader;
+mod lump_ref;
+mod metadata;
+mod name_index;
+mod namespace;
+mod options;
+mod source;
+#[cfg(test)]
+mod test_util;
+mod verdict;
+mod warning;
+
+pub use builder::WadBuilder;
+pub use directory_view::DirectoryView;
+pub use editor::WadEditor;
+pub use error::WadError;
+pub use lump_reader::LumpReader;
+pub use lump_ref::{LumpRef, Lumps};
+pub use metadata::{RdInfo, RdInfoError, WadMetadata};
+pub use namespace::Namespace;
+pub use options::{WadLimits, WadOptions};
+pub use source::{MemorySource, ReaderSource, WadSource};
+pub use verdict::WadVerdict;
+pub use warning::WadWarning;
+
+/// The main user-facing types, for glob importing with `use wad::prelude::*`.
+pub mod prelude {
+	pub use crate::{
+		DirectoryView, LumpRef, Namespace, Wad, WadBuilder, WadDirectoryEntry, WadEditor, WadError,
+		WadOptions, WadSource, WadString, WadType, WadWarning,
+	};
+}
 
 /// Where's All the Data?
+///
+/// The WAD's data is read from `S`, which is a [`File`] unless loaded from
+/// some other [`WadSource`].
 #[derive(Debug)]
-pub struct Wad {
-	file: File,
+pub struct Wad<S = File> {
+	source: S,
 	pub header: WadHeader,
 	pub directory: Vec<WadDirectoryEntry>,
+	warnings: Vec<WadWarning>,
+	cache: Mutex<cache::LumpCache>,
+	name_index: name_index::NameIndex,
+	namespaces: Vec<Namespace>,
 }
 
 impl Wad {
-	pub fn new(mut file: File) -> Result<Self, ()> {
+	/// Open and load the WAD file at `path`
+	pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, WadError> {
+		Self::options().open(path)
+	}
+
+	/// Options for loading a WAD, for behavior beyond the defaults of `new`
+	pub fn options() -> WadOptions {
+		WadOptions::new()
+	}
+
+	/// Overwrite part of a lump's data in place. `range` is relative to the start
+	/// of the lump and must lie within it, and `bytes` must be the same length as
+	/// `range`; lumps can't be resized this way. The file must be writable; open
+	/// it with [`WadOptions::writable`], as [`Wad::open`] opens it read-only.
+	///
+	/// The lump cache is cleared, so [`Wad::cached_lump`] sees the new data.
+	pub fn patch_lump_bytes(
+		&self,
+		entry: &WadDirectoryEntry,
+		range: Range<usize>,
+		bytes: &[u8],
+	) -> std::io::Result<()> {
+		let (offset_bytes, size_bytes) = entry.extent()?;
+
+		if range.start > range.end || range.end as u64 > size_bytes {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::InvalidInput,
+				"patch range is outside of the lump",
+			));
+		}
+		if bytes.len() != range.len() {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::InvalidInput,
+				"patch length does not match range length",
+			));
+		}
+
+		source::write_all_at(&self.source, bytes, offset_bytes + range.start as u64)?;
+		self.clear_cache();
+		Ok(())
+	}
+}
+
+impl<R: Read + Seek> Wad<ReaderSource<R>> {
+	/// Load a WAD from any seekable reader
+	pub fn from_reader(reader: R) -> Result<Self, WadError> {
+		Wad::new(ReaderSource::new(reader))
+	}
+}
+
+impl<B: AsRef<[u8]>> Wad<MemorySource<B>> {
+	/// Load a WAD from bytes in memory. Lumps are read directly from `bytes`.
+	///
+	/// Any `AsRef<[u8]>` works, so a memory-mapped file (e.g. a `memmap2::Mmap`)
+	/// can be passed in to avoid reading the whole file up front.
+	pub fn from_bytes(bytes: B) -> Result<Self, WadError> {
+		Wad::new(MemorySource::new(bytes))
+	}
+
+	/// The contents of a lump, borrowed directly from the WAD's bytes without
+	/// copying
+	pub fn lump_bytes(&self, entry: &WadDirectoryEntry) -> std::io::Result<&[u8]> {
+		let (offset_bytes, size_bytes) = entry.extent()?;
+		let start = offset_bytes as usize;
+		let end = start + size_bytes as usize;
+
+		self.source
+			.as_bytes()
+			.get(start..end)
+			.ok_or_else(|| std::io::Error::from(std::io::ErrorKind::UnexpectedEof))
+	}
+}
+
+impl<S: WadSource> Wad<S> {
+	pub fn new(source: S) -> Result<Self, WadError> {
+		Self::load(source, &WadOptions::new())
+	}
+
+	fn load(source: S, options: &WadOptions) -> Result<Self, WadError> {
 		let mut header_buf = [0; WadHeader::SIZE_BYTES];
-		file.seek(SeekFrom::Start(0))
-			.and_then(|_| file.read_exact(&mut header_buf))
-			.map_err(|_| ())?;
+		source
+			.read_exact_at(&mut header_buf, 0)
+			.map_err(|err| WadError::from_io(err, WadError::TruncatedHeader))?;
 
 		let header = WadHeader::new(header_buf)?;
 
-		let mut directory_buf = vec![0; header.num_lumps as usize * WadDirectoryEntry::SIZE_BYTES];
-		file.seek(SeekFrom::Start(header.directory_offset_bytes as u64))
-			.and_then(|_| file.read_exact(&mut directory_buf))
-			.map_err(|_| ())?;
+		let num_lumps = usize::try_from(header.num_lumps)
+			.map_err(|_| WadError::InvalidLumpCount(header.num_lumps))?;
+		if let Some(max) = options.limits.max_lumps {
+			if num_lumps > max {
+				return Err(WadError::TooManyLumps { num_lumps, max });
+			}
+		}
+
+		// Check the directory is within the source before allocating space for
+		// it, so a bogus header can't cause a huge allocation
+		let directory_size = num_lumps * WadDirectoryEntry::SIZE_BYTES;
+		let directory_end = u64::try_from(header.directory_offset_bytes)
+			.ok()
+			.and_then(|offset| offset.checked_add(directory_size as u64));
+		if directory_end.is_none_or(|end| end > source.size_bytes().unwrap_or(u64::MAX)) {
+			return Err(WadError::TruncatedDirectory);
+		}
+
+		let mut directory_buf = vec![0; directory_size];
+		source
+			.read_exact_at(&mut directory_buf, header.directory_offset_bytes as u64)
+			.map_err(|err| WadError::from_io(err, WadError::TruncatedDirectory))?;
 
 		let directory: Vec<WadDirectoryEntry> = directory_buf
 			.chunks(WadDirectoryEntry::SIZE_BYTES)
 			.map(|chunk| chunk.try_into().unwrap())
-			.map(WadDirectoryEntry::new)
+			.map(|data| match options.lenient {
+				true => Ok(WadDirectoryEntry::new_lossy(data)),
+				false => WadDirectoryEntry::new(data),
+			})
 			.collect::<Result<_, _>>()?;
 
+		if !options.lenient {
+			let invalid = directory
+				.iter()
+				.enumerate()
+				.find(|(_, entry)| entry.offset_bytes < 0 || entry.size_bytes < 0);
+
+			if let Some((index, entry)) = invalid {
+				return Err(WadError::InvalidLumpExtent {
+					index,
+					offset_bytes: entry.offset_bytes,
+					size_bytes: entry.size_bytes,
+				});
+			}
+		}
+
+		if let Some(max) = options.limits.max_lump_bytes {
+			let too_large = directory
+				.iter()
+				.enumerate()
+				.filter_map(|(index, entry)| Some((index, usize::try_from(entry.size_bytes).ok()?)))
+				.find(|(_, size_bytes)| *size_bytes > max);
+
+			if let Some((index, size_bytes)) = too_large {
+				return Err(WadError::LumpTooLarge {
+					index,
+					size_bytes,
+					max,
+				});
+			}
+		}
+
+		if let Some(max) = options.limits.max_total_lump_bytes {
+			let total_bytes = directory
+				.iter()
+				.map(|entry| entry.size_bytes.max(0) as u64)
+				.sum();
+
+			if total_bytes > max {
+				return Err(WadError::LumpsTooLarge { total_bytes, max });
+			}
+		}
+
+		let mut warnings = Vec::new();
+		for (index, entry) in directory.iter().enumerate() {
+			if entry.lump_name.raw_bytes().iter().any(|byte| *byte > 127) {
+				warnings.push(WadWarning::NonAsciiLumpName {
+					index,
+					lump_name: entry.lump_name,
+				});
+			}
+			if entry.offset_bytes < 0 || entry.size_bytes < 0 {
+				warnings.push(WadWarning::InvalidLumpExtent {
+					index,
+					offset_bytes: entry.offset_bytes,
+					size_bytes: entry.size_bytes,
+				});
+			}
+		}
+
 		Ok(Wad {
-			file,
+			source,
 			header,
+			warnings,
+			cache: Mutex::new(cache::LumpCache::new(options.cache_bytes)),
+			name_index: name_index::NameIndex::new(&directory),
+			namespaces: namespace::namespaces(&directory),
 			directory,
 		})
 	}
+
+	/// Stop using the WAD, returning the source it was read from
+	pub fn into_source(self) -> S {
+		self.source
+	}
+
+	/// Problems found while loading the WAD that didn't stop it from loading
+	pub fn warnings(&self) -> &[WadWarning] {
+		&self.warnings
+	}
+
+	/// The index of the lump with the given name. If there are several, the last
+	/// one in the directory is used, as it overrides the others.
+	pub fn index_of(&self, name: &str) -> Option<usize> {
+		self.indices_of(name).last().copied()
+	}
+
+	/// The indices of all lumps with the given name, in directory order
+	///
+	/// Lookups by name use an index built when the WAD is loaded. If you modify
+	/// `directory`, call [`Wad::rebuild_index`] afterwards; until then, lookups
+	/// may give out-of-date results (but never panic).
+	pub fn indices_of(&self, name: &str) -> &[usize] {
+		self.name_index.get(name.as_bytes())
+	}
+
+	/// Rebuild the indices used for lookups by name and namespace, after
+	/// modifying `directory`. The lump cache is keyed by directory index, so it
+	/// is cleared too.
+	pub fn rebuild_index(&mut self) {
+		self.name_index = name_index::NameIndex::new(&self.directory);
+		self.namespaces = namespace::namespaces(&self.directory);
+		self.clear_cache();
+	}
+
+	/// Read the lump with the given name, using the lump cache (see
+	/// [`WadOptions::cache`]) to avoid reading it again next time. Returns `None`
+	/// if there is no such lump.
+	pub fn cached_lump(&self, name: &str) -> std::io::Result<Option<Arc<[u8]>>> {
+		let Some(index) = self.index_of(name) else {
+			return Ok(None);
+		};
+		// The index may be stale if `directory` was changed without rebuilding it
+		let Some(entry) = self.directory.get(index) else {
+			return Ok(None);
+		};
+
+		if let Some(data) = self.lock_cache().get(index) {
+			return Ok(Some(data));
+		}
+
+		let data: Arc<[u8]> = Arc::from(self.read_lump(entry)?);
+		self.lock_cache().insert(index, data.clone());
+		Ok(Some(data))
+	}
+
+	/// Total size of the lumps currently in the lump cache
+	pub fn cached_bytes(&self) -> usize {
+		self.lock_cache().used_bytes()
+	}
+
+	/// Remove all lumps from the lump cache
+	pub fn clear_cache(&self) {
+		self.lock_cache().clear();
+	}
+
+	fn lock_cache(&self) -> std::sync::MutexGuard<'_, cache::LumpCache> {
+		// The cache is never left inconsistent by a panic, so poisoning is ignored
+		self.cache
+			.lock()
+			.unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+
+	/// Read the contents of a lump into a newly allocated buffer
+	pub fn read_lump(&self, entry: &WadDirectoryEntry) -> std::io::Result<Vec<u8>> {
+		let (offset_bytes, size_bytes) = entry.extent()?;
+
+		// Check the lump is within the source before allocating space for it, so
+		// a bogus directory entry can't cause a huge allocation
+		if offset_bytes + size_bytes > self.source.size_bytes()? {
+			return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof));
+		}
+
+		let mut buf = vec![0; size_bytes as usize];
+		entry.read_lump(&mut buf, self)?;
+		Ok(buf)
+	}
+
+	/// Read `len` bytes of a lump, starting `offset` bytes into it. The range must
+	/// lie within the lump, or an `InvalidInput` error is returned.
+	pub fn read_lump_range(
+		&self,
+		entry: &WadDirectoryEntry,
+		offset: usize,
+		len: usize,
+	) -> std::io::Result<Vec<u8>> {
+		let (offset_bytes, size_bytes) = entry.extent()?;
+
+		let in_bounds = offset
+			.checked_add(len)
+			.is_some_and(|end| end as u64 <= size_bytes);
+		if !in_bounds {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::InvalidInput,
+				"range is outside of the lump",
+			));
+		}
+
+		let mut buf = vec![0; len];
+		self.source
+			.read_exact_at(&mut buf, offset_bytes + offset as u64)?;
+		Ok(buf)
+	}
+
+	/// A reader over the contents of a lump, which implements `Read` and `Seek`.
+	/// Returns an `InvalidData` error if the lump has a negative offset or size.
+	pub fn lump_reader(&self, entry: &WadDirectoryEntry) -> std::io::Result<LumpReader<'_, S>> {
+		let (offset_bytes, size_bytes) = entry.extent()?;
+		Ok(LumpReader::new(&self.source, offset_bytes, size_bytes))
+	}
+
+	/// Find all lumps with names matching a glob pattern, where `?` matches any
+	/// single character and `*` matches any run of characters (e.g. `D_*`).
+	pub fn find<'a>(
+		&'a self,
+		pattern: &'a str,
+	) -> impl Iterator<Item = &'a WadDirectoryEntry> + 'a {
+		self.directory
+			.iter()
+			.filter(move |entry| entry.lump_name.matches(pattern))
+	}
+
+	/// Find all lumps for which `predicate` returns true. The predicate is given
+	/// each lump's name, size, and namespace.
+	pub fn filter_lumps<'a, F>(
+		&'a self,
+		mut predicate: F,
+	) -> impl Iterator<Item = &'a WadDirectoryEntry> + 'a
+	where
+		F: FnMut(&WadString, i32, Namespace) -> bool + 'a,
+	{
+		self.directory
+			.iter()
+			.zip(self.namespaces.iter().copied())
+			.filter(move |(entry, namespace)| {
+				predicate(&entry.lump_name, entry.size_bytes, *namespace)
+			})
+			.map(|(entry, _)| entry)
+	}
+
+	/// The namespace of each entry in the directory, in directory order. These
+	/// are worked out when the WAD is loaded; if you modify `directory`, call
+	/// [`Wad::rebuild_index`] afterwards.
+	pub fn namespaces(&self) -> &[Namespace] {
+		&self.namespaces
+	}
+
+	/// Iterate over all lumps in a namespace (e.g. all sprites), not including
+	/// the marker lumps.
+	pub fn lumps_in(&self, namespace: Namespace) -> impl Iterator<Item = &WadDirectoryEntry> {
+		self.filter_lumps(move |_, _, lump_namespace| lump_namespace == namespace)
+	}
+
+	/// Find the lump with the given name in a namespace, e.g. the flat named
+	/// `FLOOR4_8`. If there are several, the last one in the directory is used.
+	pub fn find_in_namespace(
+		&self,
+		name: &str,
+		namespace: Namespace,
+	) -> Option<&WadDirectoryEntry> {
+		self.indices_of(name)
+			.iter()
+			.rev()
+			.filter(|index| self.namespaces.get(**index) == Some(&namespace))
+			.find_map(|index| self.directory.get(*index))
+	}
 }
 
 #[derive(Debug)]
@@ -50,17 +435,26 @@ pub struct WadHeader {
 impl WadHeader {
 	pub const SIZE_BYTES: usize = 12;
 
-	fn new(data: [u8; 12]) -> Result<Self, ()> {
+	fn new(data: [u8; 12]) -> Result<Self, WadError> {
 		Ok(WadHeader {
 			iwad_or_pwad: WadType::new(data[0..4].try_into().unwrap())?,
 			num_lumps: i32::from_le_bytes(data[4..8].try_into().unwrap()),
 			directory_offset_bytes: i32::from_le_bytes(data[8..12].try_into().unwrap()),
 		})
 	}
+
+	pub fn to_bytes(&self) -> [u8; Self::SIZE_BYTES] {
+		let mut data = [0; Self::SIZE_BYTES];
+		data[0..4].copy_from_slice(&self.iwad_or_pwad.to_bytes());
+		data[4..8].copy_from_slice(&self.num_lumps.to_le_bytes());
+		data[8..12].copy_from_slice(&self.directory_offset_bytes.to_le_bytes());
+		data
+	}
 }
 
 /// Either IWAD or PWAD
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
 pub enum WadType {
 	IWAD,
 	PWAD,
@@ -69,16 +463,23 @@ pub enum WadType {
 impl WadType {
 	pub const SIZE_BYTES: usize = 4;
 
-	pub fn new(data: [u8; Self::SIZE_BYTES]) -> Result<Self, ()> {
+	pub fn new(data: [u8; Self::SIZE_BYTES]) -> Result<Self, WadError> {
 		match &data {
 			b"IWAD" => Ok(Self::IWAD),
 			b"PWAD" => Ok(Self::PWAD),
-			_ => Err(()),
+			_ => Err(WadError::InvalidWadType(data)),
+		}
+	}
+
+	pub fn to_bytes(&self) -> [u8; Self::SIZE_BYTES] {
+		match self {
+			Self::IWAD => *b"IWAD",
+			Self::PWAD => *b"PWAD",
 		}
 	}
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct WadDirectoryEntry {
 	pub offset_bytes: i32,
 	pub size_bytes: i32,
@@ -88,35 +489,82 @@ pub struct WadDirectoryEntry {
 impl WadDirectoryEntry {
 	pub const SIZE_BYTES: usize = 16;
 
-	pub fn new(data: [u8; Self::SIZE_BYTES]) -> Result<Self, ()> {
+	pub fn new(data: [u8; Self::SIZE_BYTES]) -> Result<Self, WadError> {
 		Ok(WadDirectoryEntry {
 			offset_bytes: i32::from_le_bytes(data[0..4].try_into().unwrap()),
 			size_bytes: i32::from_le_bytes(data[4..8].try_into().unwrap()),
-			lump_name: WadString::new(data[8..16].try_into().unwrap()).unwrap(),
+			lump_name: WadString::new(data[8..16].try_into().unwrap())?,
 		})
 	}
 
+	/// Like `new`, but keeps a lump name with non-ASCII characters as-is (see
+	/// [`WadString::new_lossy`]) instead of failing.
+	pub fn new_lossy(data: [u8; Self::SIZE_BYTES]) -> Self {
+		WadDirectoryEntry {
+			offset_bytes: i32::from_le_bytes(data[0..4].try_into().unwrap()),
+			size_bytes: i32::from_le_bytes(data[4..8].try_into().unwrap()),
+			lump_name: WadString::new_lossy(data[8..16].try_into().unwrap()),
+		}
+	}
+
+	pub fn to_bytes(&self) -> [u8; Self::SIZE_BYTES] {
+		let mut data = [0; Self::SIZE_BYTES];
+		data[0..4].copy_from_slice(&self.offset_bytes.to_le_bytes());
+		data[4..8].copy_from_slice(&self.size_bytes.to_le_bytes());
+		data[8..16].copy_from_slice(self.lump_name.raw_bytes());
+		data
+	}
+
 	/// Virtual lumps have a size of zero and only appear in the directory
 	pub fn is_virtual(&self) -> bool {
 		self.size_bytes == 0
 	}
 
-	/// Read the contents of a lump into a buffer. The buffer's size must equal `size_bytes`.
-	pub fn read_lump(&self, buf: &mut [u8], wadfile: &Wad) -> std::io::Result<()> {
-		assert!(buf.len() == self.size_bytes as usize);
+	/// The lump's offset and size, or an `InvalidData` error if either is
+	/// negative
+	pub(crate) fn extent(&self) -> std::io::Result<(u64, u64)> {
+		match (
+			u64::try_from(self.offset_bytes),
+			u64::try_from(self.size_bytes),
+		) {
+			(Ok(offset_bytes), Ok(size_bytes)) => Ok((offset_bytes, size_bytes)),
+			_ => Err(std::io::Error::new(
+				std::io::ErrorKind::InvalidData,
+				"lump has a negative offset or size",
+			)),
+		}
+	}
 
-		let mut file = &wadfile.file;
+	/// Read the contents of a lump into a buffer. The buffer's size must equal
+	/// `size_bytes`, or an `InvalidInput` error is returned.
+	pub fn read_lump<S: WadSource>(&self, buf: &mut [u8], wadfile: &Wad<S>) -> std::io::Result<()> {
+		self.read_lump_from(buf, &wadfile.source)
+	}
 
-		file.seek(SeekFrom::Start(self.offset_bytes as u64))?;
-		file.read_exact(buf)?;
+	pub(crate) fn read_lump_from<S: WadSource>(
+		&self,
+		buf: &mut [u8],
+		source: &S,
+	) -> std::io::Result<()> {
+		let (offset_bytes, size_bytes) = self.extent()?;
 
-		Ok(())
+		if buf.len() as u64 != size_bytes {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::InvalidInput,
+				"buffer size does not match lump size",
+			));
+		}
+
+		source.read_exact_at(buf, offset_bytes)
 	}
 }
 
 /// The string format used for the name of lumps. It is an 8-byte long ASCII
 /// string, right-padded with null bytes.
-#[derive(Debug, PartialEq)]
+///
+/// Comparison and hashing only look at the bytes before the first null, as any
+/// bytes after it aren't part of the name.
+#[derive(Clone, Copy, Debug)]
 pub struct WadString {
 	bytes: [u8; 8],
 }
@@ -124,14 +572,84 @@ pub struct WadString {
 impl WadString {
 	pub const SIZE_BYTES: usize = 8;
 
-	pub fn new(bytes: [u8; Self::SIZE_BYTES]) -> Result<WadString, ()> {
+	pub fn new(bytes: [u8; Self::SIZE_BYTES]) -> Result<WadString, WadError> {
 		// Check for non-ASCII characters
 		if bytes.iter().any(|byte| *byte > 127) {
-			return Err(());
+			return Err(WadError::InvalidLumpName(bytes));
 		}
 
 		Ok(WadString { bytes })
 	}
+
+	/// Like `new`, but keeps non-ASCII bytes as-is instead of failing. These
+	/// bytes are escaped when the string is displayed.
+	pub fn new_lossy(bytes: [u8; Self::SIZE_BYTES]) -> WadString {
+		WadString { bytes }
+	}
+
+	/// The raw bytes of the string, including any null padding
+	pub fn raw_bytes(&self) -> &[u8; Self::SIZE_BYTES] {
+		&self.bytes
+	}
+
+	/// The bytes of the string, up to but not including the null padding
+	pub fn trimmed_bytes(&self) -> &[u8] {
+		let len = self
+			.bytes
+			.iter()
+			.position(|byte| *byte == 0)
+			.unwrap_or(Self::SIZE_BYTES);
+		&self.bytes[..len]
+	}
+
+	/// Check if the string matches a glob pattern, where `?` matches any single
+	/// character and `*` matches any run of characters. Case is ignored.
+	pub fn matches(&self, pattern: &str) -> bool {
+		let name = self.trimmed_bytes();
+		let pattern = pattern.as_bytes();
+
+		// Backtracks only to the most recent `*`, so the time taken is bounded by
+		// the name's length times the pattern's, however many stars there are
+		let (mut n, mut p) = (0, 0);
+		let mut backtrack = None;
+		while n < name.len() {
+			match pattern.get(p) {
+				Some(b'*') => {
+					backtrack = Some((n, p));
+					p += 1;
+				}
+				Some(byte) if *byte == b'?' || byte.eq_ignore_ascii_case(&name[n]) => {
+					n += 1;
+					p += 1;
+				}
+				_ => match backtrack {
+					// Let the last `*` match one more character
+					Some((star_n, star_p)) => {
+						backtrack = Some((star_n + 1, star_p));
+						n = star_n + 1;
+						p = star_p + 1;
+					}
+					None => return false,
+				},
+			}
+		}
+
+		pattern[p..].iter().all(|byte| *byte == b'*')
+	}
+
+	/// Compare with another string, ignoring ASCII case
+	pub fn eq_ignore_case(&self, other: &WadString) -> bool {
+		self.trimmed_bytes()
+			.eq_ignore_ascii_case(other.trimmed_bytes())
+	}
+
+	/// A copy of the string with ASCII letters uppercased, which is how lump
+	/// names are normally stored
+	pub fn to_uppercase(&self) -> WadString {
+		let mut bytes = self.bytes;
+		bytes.make_ascii_uppercase();
+		WadString { bytes }
+	}
 }
 
 impl Display for WadString {
@@ -140,17 +658,445 @@ impl Display for WadString {
 			.iter()
 			.map_while(|byte| match byte {
 				0 => None, // end of string
-				1..=127 => Some(*byte as char),
-				_ => panic!("Invalid (non-ASCII) character in {}", type_name::<Self>()),
+				1..=127 => Some((*byte as char).to_string()),
+				_ => Some(format!("\\x{:02X}", byte)),
 			})
 			.collect::<String>()
 			.fmt(f)
 	}
 }
 
+impl PartialEq for WadString {
+	fn eq(&self, other: &Self) -> bool {
+		self.trimmed_bytes() == other.trimmed_bytes()
+	}
+}
+
+impl Eq for WadString {}
+
+impl std::hash::Hash for WadString {
+	fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+		self.trimmed_bytes().hash(state);
+	}
+}
+
+impl PartialOrd for WadString {
+	fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for WadString {
+	fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+		self.trimmed_bytes().cmp(other.trimmed_bytes())
+	}
+}
+
+/// Compares the string up to its null padding. Like comparing two `WadString`s,
+/// this is case-sensitive; use [`WadString::eq_ignore_case`] or look lumps up by
+/// name to ignore case.
+impl PartialEq<str> for WadString {
+	fn eq(&self, other: &str) -> bool {
+		self.trimmed_bytes() == other.as_bytes()
+	}
+}
+
+impl PartialEq<&str> for WadString {
+	fn eq(&self, other: &&str) -> bool {
+		self == *other
+	}
+}
+
+/// Uppercases the string and pads it with nulls, so `"playpal"` becomes the
+/// `PLAYPAL` found in the directory. Fails if it is empty, longer than 8 bytes,
+/// or contains non-ASCII or null characters.
+impl TryFrom<&str> for WadString {
+	type Error = WadError;
+
+	fn try_from(value: &str) -> Result<Self, Self::Error> {
+		if value.is_empty() {
+			return Err(WadError::EmptyLumpName);
+		}
+		if value.len() > Self::SIZE_BYTES {
+			return Err(WadError::LumpNameTooLong(value.to_owned()));
+		}
+
+		let mut bytes = [0; Self::SIZE_BYTES];
+		bytes[..value.len()].copy_from_slice(value.as_bytes());
+		bytes.make_ascii_uppercase();
+
+		if value.contains('\0') {
+			return Err(WadError::InvalidLumpName(bytes));
+		}
+		WadString::new(bytes)
+	}
+}
+
+impl AsRef<[u8]> for WadString {
+	/// The bytes of the string, up to but not including the null padding
+	fn as_ref(&self) -> &[u8] {
+		self.trimmed_bytes()
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::test_util::temp_file;
+
+	/// Write a PWAD containing the given lumps to a temporary file.
+	fn test_wad_file(lumps: &[(&[u8], &[u8])]) -> File {
+		let mut data = Vec::new();
+		let mut directory = Vec::new();
+		for (name, lump) in lumps {
+			directory.extend_from_slice(&(12 + data.len() as i32).to_le_bytes());
+			directory.extend_from_slice(&(lump.len() as i32).to_le_bytes());
+			let mut name_bytes = [0u8; 8];
+			name_bytes[..name.len()].copy_from_slice(name);
+			directory.extend_from_slice(&name_bytes);
+			data.extend_from_slice(lump);
+		}
+
+		let mut bytes = Vec::new();
+		bytes.extend_from_slice(b"PWAD");
+		bytes.extend_from_slice(&(lumps.len() as i32).to_le_bytes());
+		bytes.extend_from_slice(&(12 + data.len() as i32).to_le_bytes());
+		bytes.extend_from_slice(&data);
+		bytes.extend_from_slice(&directory);
+
+		temp_file(&bytes)
+	}
+
+	#[test]
+	fn wad_namespace_lookup() {
+		let mut wad = Wad::new(test_wad_file(&[
+			(b"F_START", b""),
+			(b"STEP1", b"flat"),
+			(b"F_END", b""),
+			(b"P_START", b""),
+			(b"STEP1", b"patch"),
+			(b"WALL00_1", b"patch"),
+			(b"P_END", b""),
+		]))
+		.unwrap();
+
+		let flat = wad.find_in_namespace("STEP1", Namespace::Flats).unwrap();
+		assert_eq!(flat.size_bytes, 4);
+		let patch = wad.find_in_namespace("STEP1", Namespace::Patches).unwrap();
+		assert_eq!(patch.size_bytes, 5);
+		assert!(wad.find_in_namespace("STEP1", Namespace::Sprites).is_none());
+
+		let patches = wad
+			.lumps_in(Namespace::Patches)
+			.map(|entry| entry.lump_name.to_string())
+			.collect::<Vec<_>>();
+		assert_eq!(patches, ["STEP1", "WALL00_1"]);
+
+		wad.directory.drain(..3);
+		wad.rebuild_index();
+		assert_eq!(wad.namespaces()[1], Namespace::Patches);
+		assert!(wad.find_in_namespace("STEP1", Namespace::Flats).is_none());
+
+		// Stale lookups after changing the directory don't panic
+		wad.directory.clear();
+		assert!(wad.find_in_namespace("STEP1", Namespace::Patches).is_none());
+		assert!(wad.lump_named("STEP1").is_none());
+		assert!(wad.map_lumps("STEP1").is_none());
+	}
+
+	#[test]
+	fn wad_name_index() {
+		let mut wad = Wad::new(test_wad_file(&[
+			(b"PLAYPAL", b""),
+			(b"DEMO1", b""),
+			(b"PLAYPAL", b""),
+		]))
+		.unwrap();
+
+		assert_eq!(wad.index_of("PLAYPAL"), Some(2));
+		assert_eq!(wad.indices_of("PLAYPAL"), [0, 2]);
+		assert_eq!(wad.indices_of("playpal"), [0, 2]);
+		assert_eq!(wad.indices_of("COLORMAP"), []);
+		assert_eq!(wad.indices_of("TOOLONGNAME"), []);
+
+		wad.directory.remove(0);
+		wad.rebuild_index();
+		assert_eq!(wad.indices_of("PLAYPAL"), [1]);
+	}
+
+	#[test]
+	fn wad_patch_lump_bytes() {
+		let wad = Wad::new(test_wad_file(&[
+			(b"PNAMES", b"WALL00_1"),
+			(b"TROOA1", b"\x10\x00\x20\x00"),
+		]))
+		.unwrap();
+		let entry = &wad.directory[0];
+
+		wad.patch_lump_bytes(entry, 4..8, b"02_3").unwrap();
+		wad.patch_lump_bytes(entry, 6..9, b"XYZ").unwrap_err();
+		wad.patch_lump_bytes(entry, 0..2, b"XYZ").unwrap_err();
+
+		let mut buf = [0u8; 8];
+		entry.read_lump(&mut buf, &wad).unwrap();
+		assert_eq!(&buf, b"WALL02_3");
+
+		let mut buf = [0u8; 4];
+		wad.directory[1].read_lump(&mut buf, &wad).unwrap();
+		assert_eq!(&buf, b"\x10\x00\x20\x00");
+
+		let mut negative = wad.directory[1].clone();
+		negative.offset_bytes = -1;
+		let err = wad.patch_lump_bytes(&negative, 0..1, b"X").unwrap_err();
+		assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+		negative.offset_bytes = wad.directory[1].offset_bytes;
+		negative.size_bytes = -1;
+		let err = wad.patch_lump_bytes(&negative, 0..1, b"X").unwrap_err();
+		assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+		// Opening by path is read-only unless the writable option is set
+		let mut bytes = Vec::new();
+		wad.into_source().read_to_end(&mut bytes).unwrap();
+		let path = crate::test_util::temp_path(&bytes);
+
+		let wad = Wad::open(&path).unwrap();
+		wad.patch_lump_bytes(&wad.directory[0], 0..4, b"DOOR")
+			.unwrap_err();
+
+		let wad = Wad::options().writable(true).open(&path).unwrap();
+		wad.patch_lump_bytes(&wad.directory[0], 0..4, b"DOOR")
+			.unwrap();
+		assert_eq!(wad.read_lump(&wad.directory[0]).unwrap(), b"DOOR02_3");
+		std::fs::remove_file(&path).ok();
+	}
+
+	#[test]
+	fn wad_options_lenient_and_limits() {
+		let lumps: &[(&[u8], &[u8])] = &[(b"PLAYPAL", b"\0\0\0"), (b"DEMO\xC1", b"\0")];
+
+		Wad::new(test_wad_file(lumps)).unwrap_err(); // Panic on Ok
+
+		let wad = Wad::options()
+			.lenient(true)
+			.load(test_wad_file(lumps))
+			.unwrap();
+		assert_eq!(format!("{}", wad.directory[1].lump_name), "DEMO\\xC1");
+		assert_eq!(
+			wad.warnings(),
+			[WadWarning::NonAsciiLumpName {
+				index: 1,
+				lump_name: wad.directory[1].lump_name,
+			}]
+		);
+
+		let limits = WadLimits {
+			max_lumps: Some(1),
+			..WadLimits::default()
+		};
+		Wad::options()
+			.lenient(true)
+			.limits(limits)
+			.load(test_wad_file(lumps))
+			.unwrap_err();
+
+		let limits = WadLimits {
+			max_lump_bytes: Some(2),
+			..WadLimits::default()
+		};
+		Wad::options()
+			.lenient(true)
+			.limits(limits)
+			.load(test_wad_file(lumps))
+			.unwrap_err();
+	}
+
+	#[test]
+	fn wad_new_reports_failure_cause() {
+		let result = Wad::new(temp_file(b"PWAD\x01\0"));
+		assert!(matches!(result, Err(WadError::TruncatedHeader)));
+
+		let result = Wad::new(temp_file(b"ZWAD\0\0\0\0\x0C\0\0\0"));
+		assert!(matches!(result, Err(WadError::InvalidWadType(bytes)) if &bytes == b"ZWAD"));
+
+		let result = Wad::new(temp_file(b"PWAD\xFF\xFF\xFF\xFF\x0C\0\0\0"));
+		assert!(matches!(result, Err(WadError::InvalidLumpCount(-1))));
+
+		let result = Wad::new(temp_file(b"PWAD\x01\0\0\0\x0C\0\0\0"));
+		assert!(matches!(result, Err(WadError::TruncatedDirectory)));
+	}
+
+	#[test]
+	fn wad_negative_lump_extent() {
+		let mut bytes = Vec::new();
+		bytes.extend_from_slice(b"PWAD\x01\0\0\0\x0C\0\0\0");
+		bytes.extend_from_slice(b"\xFF\xFF\xFF\xFF\x08\0\0\0BADLUMP\0");
+
+		let result = Wad::options().untrusted().load(temp_file(&bytes));
+		assert!(matches!(
+			result,
+			Err(WadError::InvalidLumpExtent {
+				index: 0,
+				offset_bytes: -1,
+				size_bytes: 8,
+			})
+		));
+
+		let wad = Wad::options()
+			.lenient(true)
+			.limits(WadLimits::untrusted())
+			.load(temp_file(&bytes))
+			.unwrap();
+		assert_eq!(
+			wad.warnings(),
+			[WadWarning::InvalidLumpExtent {
+				index: 0,
+				offset_bytes: -1,
+				size_bytes: 8,
+			}]
+		);
+
+		let err = wad.read_lump_range(&wad.directory[0], 4, 1).unwrap_err();
+		assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+		let err = wad.lump_reader(&wad.directory[0]).unwrap_err();
+		assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+		let err = wad.read_lump(&wad.directory[0]).unwrap_err();
+		assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+		let err = wad.directory[0].read_lump(&mut [0u8; 8], &wad).unwrap_err();
+		assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+		let wad = Wad::options()
+			.lenient(true)
+			.load(MemorySource::new(&bytes))
+			.unwrap();
+		let err = wad.lump_bytes(&wad.directory[0]).unwrap_err();
+		assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+	}
+
+	#[test]
+	fn wad_from_reader() {
+		let mut file = test_wad_file(&[(b"PLAYPAL", b"\x01\x02\x03")]);
+		let mut bytes = Vec::new();
+		file.read_to_end(&mut bytes).unwrap();
+
+		let wad = Wad::from_reader(std::io::Cursor::new(bytes)).unwrap();
+		assert_eq!(wad.directory[0].lump_name.to_string(), "PLAYPAL");
+
+		let mut buf = [0u8; 3];
+		wad.directory[0].read_lump(&mut buf, &wad).unwrap();
+		assert_eq!(buf, [1, 2, 3]);
+	}
+
+	#[test]
+	fn wad_from_bytes() {
+		let mut bytes = Vec::new();
+		bytes.extend_from_slice(b"IWAD\x01\0\0\0\x0F\0\0\0");
+		bytes.extend_from_slice(b"\x07\x08\x09");
+		bytes.extend_from_slice(b"\x0C\0\0\0\x03\0\0\0COLORMAP");
+
+		let wad = Wad::from_bytes(&bytes[..]).unwrap();
+		assert_eq!(wad.header.iwad_or_pwad, WadType::IWAD);
+
+		let mut buf = [0u8; 3];
+		wad.directory[0].read_lump(&mut buf, &wad).unwrap();
+		assert_eq!(buf, [7, 8, 9]);
+
+		assert_eq!(wad.lump_bytes(&wad.directory[0]).unwrap(), [7, 8, 9]);
+
+		let result = Wad::from_bytes(&bytes[..20]);
+		assert!(matches!(result, Err(WadError::TruncatedDirectory)));
+	}
+
+	#[test]
+	fn wad_concurrent_lump_reads() {
+		fn assert_sync<T: Sync>(_: &T) {}
+
+		let lumps: Vec<(Vec<u8>, Vec<u8>)> = (0..16)
+			.map(|i| (format!("LUMP{}", i).into_bytes(), vec![i as u8; 4096]))
+			.collect();
+		let lumps: Vec<(&[u8], &[u8])> = lumps.iter().map(|(n, d)| (&n[..], &d[..])).collect();
+		let wad = Wad::new(test_wad_file(&lumps)).unwrap();
+		assert_sync(&wad);
+
+		std::thread::scope(|scope| {
+			for _ in 0..4 {
+				scope.spawn(|| {
+					for _ in 0..8 {
+						for (i, entry) in wad.directory.iter().enumerate() {
+							let mut buf = vec![0u8; entry.size_bytes as usize];
+							entry.read_lump(&mut buf, &wad).unwrap();
+							assert!(buf.iter().all(|byte| *byte == i as u8));
+						}
+					}
+				});
+			}
+		});
+	}
+
+	#[test]
+	fn wad_cached_lump() {
+		let file = test_wad_file(&[(b"PNAMES", b"\x01\0\0\0"), (b"PNAMES", b"\x02\0\0\0")]);
+		let wad = Wad::options().cache(1024).load(file).unwrap();
+
+		let data = wad.cached_lump("PNAMES").unwrap().unwrap();
+		assert_eq!(&data[..], b"\x02\0\0\0");
+		assert!(wad.cached_lump("TEXTURE1").unwrap().is_none());
+
+		let data = wad.cached_lump("PNAMES").unwrap().unwrap();
+		assert_eq!(&data[..], b"\x02\0\0\0");
+		assert_eq!(wad.cached_bytes(), 4);
+
+		wad.clear_cache();
+		assert_eq!(wad.cached_bytes(), 0);
+
+		// Patching a lump invalidates the cache, so the change is seen
+		wad.cached_lump("PNAMES").unwrap().unwrap();
+		wad.patch_lump_bytes(&wad.directory[1], 0..1, b"\x03")
+			.unwrap();
+		let data = wad.cached_lump("PNAMES").unwrap().unwrap();
+		assert_eq!(&data[..], b"\x03\0\0\0");
+
+		// Rebuilding the index after changing the directory invalidates the cache
+		let file = test_wad_file(&[(b"A", b"aaaa"), (b"B", b"bbbb")]);
+		let mut wad = Wad::options().cache(1024).load(file).unwrap();
+		wad.cached_lump("A").unwrap().unwrap();
+		wad.directory.remove(0);
+		wad.rebuild_index();
+		let data = wad.cached_lump("B").unwrap().unwrap();
+		assert_eq!(&data[..], b"bbbb");
+	}
+
+	#[test]
+	fn wad_read_lump() {
+		let wad = Wad::new(test_wad_file(&[(b"ENDOOM", b"\x41\x07\x42\x07")])).unwrap();
+		let entry = &wad.directory[0];
+
+		assert_eq!(wad.read_lump(entry).unwrap(), b"\x41\x07\x42\x07");
+
+		let err = entry.read_lump(&mut [0u8; 3], &wad).unwrap_err();
+		assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+		// A lump claiming to be far larger than the file isn't allocated
+		let mut bytes = Vec::new();
+		bytes.extend_from_slice(b"PWAD\x01\0\0\0\x0C\0\0\0");
+		bytes.extend_from_slice(b"\x0C\0\0\0\xFF\xFF\xFF\x7FWADINFO\0");
+		let wad = Wad::from_bytes(&bytes[..]).unwrap();
+		let err = wad.read_lump(&wad.directory[0]).unwrap_err();
+		assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+	}
+
+	#[test]
+	fn wad_read_lump_range() {
+		let wad = Wad::new(test_wad_file(&[(b"DEMO1", b"\x6D\x02\x00\x01\x00")])).unwrap();
+		let entry = &wad.directory[0];
+
+		assert_eq!(wad.read_lump_range(entry, 0, 2).unwrap(), [0x6D, 0x02]);
+		assert_eq!(wad.read_lump_range(entry, 3, 2).unwrap(), [0x01, 0x00]);
+		assert!(wad.read_lump_range(entry, 5, 0).unwrap().is_empty());
+
+		let err = wad.read_lump_range(entry, 4, 2).unwrap_err();
+		assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+		wad.read_lump_range(entry, usize::MAX, 2).unwrap_err();
+	}
 
 	#[test]
 	fn wad_header_from_bytes() {
@@ -217,4 +1163,84 @@ mod tests {
 		let wad_str = WadString::new(*b"DEMO1\0\0\0").unwrap();
 		assert_eq!(format!("{}", wad_str), "DEMO1");
 	}
+
+	#[test]
+	fn wad_string_matches_glob() {
+		let wad_str = WadString::new(*b"MAP01\0\0\0").unwrap();
+		assert!(wad_str.matches("MAP??"));
+		assert!(wad_str.matches("MAP*"));
+		assert!(wad_str.matches("*01"));
+		assert!(!wad_str.matches("MAP?"));
+		assert!(!wad_str.matches("D_*"));
+
+		let wad_str = WadString::new(*b"D_RUNNIN").unwrap();
+		assert!(wad_str.matches("D_*"));
+		assert!(wad_str.matches("*"));
+		assert!(wad_str.matches("*_*N*"));
+		assert!(!wad_str.matches("*_*X*"));
+
+		// Many stars don't take exponential time
+		assert!(!wad_str.matches(&format!("{}Z", "*".repeat(24))));
+	}
+
+	#[test]
+	fn wad_string_traits() {
+		let wad_str = WadString::try_from("PLAYPAL").unwrap();
+		assert_eq!(wad_str, WadString::new(*b"PLAYPAL\0").unwrap());
+		assert_eq!(wad_str, "PLAYPAL");
+		assert!(wad_str != "PLAYPA");
+		assert_eq!(wad_str.as_ref(), b"PLAYPAL");
+
+		let mut counts = std::collections::HashMap::new();
+		*counts.entry(wad_str).or_insert(0) += 1;
+		*counts
+			.entry(WadString::try_from("PLAYPAL").unwrap())
+			.or_insert(0) += 1;
+		assert_eq!(counts[&wad_str], 2);
+
+		assert!(WadString::try_from("COLORMAP").unwrap() < wad_str);
+
+		// Bytes after the null terminator aren't part of the name
+		let with_garbage = WadString::new(*b"DEMO1\0XY").unwrap();
+		let demo = WadString::try_from("DEMO1").unwrap();
+		assert_eq!(with_garbage, demo);
+		assert!(with_garbage < wad_str);
+		*counts.entry(with_garbage).or_insert(0) += 1;
+		*counts.entry(demo).or_insert(0) += 1;
+		assert_eq!(counts[&demo], 2);
+
+		let result = WadString::try_from("TOOLONGNAME");
+		assert!(matches!(result, Err(WadError::LumpNameTooLong(_))));
+		WadString::try_from("MÜS").unwrap_err();
+		WadString::try_from("A\0B").unwrap_err();
+		let result = WadString::try_from("");
+		assert!(matches!(result, Err(WadError::EmptyLumpName)));
+	}
+
+	#[test]
+	fn wad_string_case_insensitive() {
+		let directory_name = WadString::new(*b"PLAYPAL\0").unwrap();
+		assert_eq!(WadString::try_from("playpal").unwrap(), directory_name);
+		assert!(directory_name.matches("play*"));
+
+		// Comparisons with other strings are case-sensitive, and agree with each
+		// other
+		let lowercase = WadString::new(*b"playpal\0").unwrap();
+		assert!(lowercase != directory_name);
+		assert!(lowercase != "PLAYPAL");
+		assert!(directory_name != "playpal");
+		assert_eq!(directory_name, "PLAYPAL");
+		assert!(lowercase.eq_ignore_case(&directory_name));
+		assert_eq!(lowercase.to_uppercase(), directory_name);
+	}
+
+	#[test]
+	fn wad_string_lossy_keeps_raw_bytes() {
+		let bytes = *b"TROO\xC1\0\0\0";
+		WadString::new(bytes).unwrap_err(); // Panic on Ok
+
+		let wad_str = WadString::new_lossy(bytes);
+		assert_eq!(wad_str.raw_bytes(), &bytes);
+		assert_eq!(format!("{}", wad_str), "TROO\\xC1");
+	}
 }
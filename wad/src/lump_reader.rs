@@ -0,0 +1,102 @@
+use std::io;
+use std::io::prelude::*;
+use std::io::SeekFrom;
+
+use crate::WadSource;
+
+/// A reader over the contents of a single lump, created with
+/// [`crate::Wad::lump_reader`]. Reading and seeking are bounded to the lump.
+#[derive(Debug)]
+pub struct LumpReader<'a, S> {
+	source: &'a S,
+	offset_bytes: u64,
+	size_bytes: u64,
+	position: u64,
+}
+
+impl<'a, S: WadSource> LumpReader<'a, S> {
+	pub(crate) fn new(source: &'a S, offset_bytes: u64, size_bytes: u64) -> Self {
+		LumpReader {
+			source,
+			offset_bytes,
+			size_bytes,
+			position: 0,
+		}
+	}
+
+	/// Size of the lump
+	pub fn len(&self) -> u64 {
+		self.size_bytes
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.size_bytes == 0
+	}
+}
+
+impl<S: WadSource> Read for LumpReader<'_, S> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		let remaining = self.size_bytes.saturating_sub(self.position);
+		let len = remaining.min(buf.len() as u64) as usize;
+		if len == 0 {
+			return Ok(0);
+		}
+
+		self.source
+			.read_exact_at(&mut buf[..len], self.offset_bytes + self.position)?;
+		self.position += len as u64;
+
+		Ok(len)
+	}
+}
+
+impl<S: WadSource> Seek for LumpReader<'_, S> {
+	fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+		let position = match pos {
+			SeekFrom::Start(offset) => Some(offset),
+			SeekFrom::End(offset) => self.size_bytes.checked_add_signed(offset),
+			SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+		};
+
+		match position {
+			Some(position) => {
+				self.position = position;
+				Ok(position)
+			}
+			None => Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"seek to a negative or overflowing position",
+			)),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::MemorySource;
+
+	#[test]
+	fn lump_reader_is_bounded_to_lump() {
+		let source = MemorySource::new(b"xxHELLOxx");
+		let mut reader = LumpReader::new(&source, 2, 5);
+
+		let mut contents = String::new();
+		reader.read_to_string(&mut contents).unwrap();
+		assert_eq!(contents, "HELLO");
+
+		reader.seek(SeekFrom::End(-2)).unwrap();
+		let mut buf = [0u8; 4];
+		assert_eq!(reader.read(&mut buf).unwrap(), 2);
+		assert_eq!(&buf[..2], b"LO");
+
+		reader.seek(SeekFrom::Current(-4)).unwrap();
+		reader.read_exact(&mut buf[..1]).unwrap();
+		assert_eq!(buf[0], b'E');
+
+		reader.seek(SeekFrom::Current(-10)).unwrap_err();
+
+		reader.seek(SeekFrom::Start(100)).unwrap();
+		assert_eq!(reader.read(&mut buf).unwrap(), 0);
+	}
+}
@@ -0,0 +1,202 @@
+use std::io;
+use std::ops::Range;
+
+use crate::{LumpReader, Wad, WadDirectoryEntry, WadSource, WadString};
+
+/// A lump in a particular [`Wad`], bundling its directory entry with the WAD
+/// it can be read from.
+#[derive(Debug)]
+pub struct LumpRef<'a, S> {
+	wad: &'a Wad<S>,
+	index: usize,
+}
+
+// Implemented by hand so `S` doesn't have to be `Clone`
+impl<S> Clone for LumpRef<'_, S> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
+impl<S> Copy for LumpRef<'_, S> {}
+
+impl<'a, S: WadSource> LumpRef<'a, S> {
+	/// Index of the lump in the directory
+	pub fn index(&self) -> usize {
+		self.index
+	}
+
+	pub fn entry(&self) -> &'a WadDirectoryEntry {
+		&self.wad.directory[self.index]
+	}
+
+	pub fn name(&self) -> &'a WadString {
+		&self.entry().lump_name
+	}
+
+	pub fn size_bytes(&self) -> i32 {
+		self.entry().size_bytes
+	}
+
+	pub fn is_virtual(&self) -> bool {
+		self.entry().is_virtual()
+	}
+
+	/// Read the lump's contents
+	pub fn read(&self) -> io::Result<Vec<u8>> {
+		self.wad.read_lump(self.entry())
+	}
+
+	/// A reader over the lump's contents
+	pub fn reader(&self) -> io::Result<LumpReader<'a, S>> {
+		self.wad.lump_reader(self.entry())
+	}
+}
+
+/// An iterator over a range of a [`Wad`]'s lumps, in directory order
+#[derive(Debug)]
+pub struct Lumps<'a, S> {
+	wad: &'a Wad<S>,
+	indices: Range<usize>,
+}
+
+impl<'a, S: WadSource> Iterator for Lumps<'a, S> {
+	type Item = LumpRef<'a, S>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let index = self.indices.next()?;
+		Some(LumpRef {
+			wad: self.wad,
+			index,
+		})
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.indices.size_hint()
+	}
+}
+
+impl<S: WadSource> DoubleEndedIterator for Lumps<'_, S> {
+	fn next_back(&mut self) -> Option<Self::Item> {
+		let index = self.indices.next_back()?;
+		Some(LumpRef {
+			wad: self.wad,
+			index,
+		})
+	}
+}
+
+impl<S: WadSource> ExactSizeIterator for Lumps<'_, S> {}
+
+/// Names of the lumps that can follow a map's marker lump
+const MAP_LUMP_NAMES: &[&[u8]] = &[
+	b"THINGS",
+	b"LINEDEFS",
+	b"SIDEDEFS",
+	b"VERTEXES",
+	b"SEGS",
+	b"SSECTORS",
+	b"NODES",
+	b"SECTORS",
+	b"REJECT",
+	b"BLOCKMAP",
+	b"BEHAVIOR",
+	b"SCRIPTS",
+	b"TEXTMAP",
+	b"ZNODES",
+	b"DIALOGUE",
+	b"ENDMAP",
+];
+
+impl<S: WadSource> Wad<S> {
+	/// Iterate over all lumps in the directory
+	pub fn lumps(&self) -> Lumps<'_, S> {
+		self.lumps_range(0..self.directory.len())
+	}
+
+	/// Iterate over the lumps with directory indices in `range`. The range is
+	/// clamped to the directory.
+	pub fn lumps_range(&self, range: Range<usize>) -> Lumps<'_, S> {
+		let end = range.end.min(self.directory.len());
+		Lumps {
+			wad: self,
+			indices: range.start.min(end)..end,
+		}
+	}
+
+	/// The lump at `index` in the directory
+	pub fn lump(&self, index: usize) -> Option<LumpRef<'_, S>> {
+		(index < self.directory.len()).then_some(LumpRef { wad: self, index })
+	}
+
+	/// The lump with the given name (see [`Wad::index_of`])
+	pub fn lump_named(&self, name: &str) -> Option<LumpRef<'_, S>> {
+		self.index_of(name).and_then(|index| self.lump(index))
+	}
+
+	/// The lumps that make up the map with the given marker name (e.g. `E1M1`),
+	/// not including the marker itself: everything from the marker up to the
+	/// next lump that isn't a map data lump.
+	pub fn map_lumps(&self, map_name: &str) -> Option<Lumps<'_, S>> {
+		let start = self.index_of(map_name)? + 1;
+		let len = self
+			.directory
+			.get(start..)?
+			.iter()
+			.take_while(|entry| is_map_lump_name(entry.lump_name.trimmed_bytes()))
+			.count();
+
+		Some(self.lumps_range(start..start + len))
+	}
+}
+
+fn is_map_lump_name(name: &[u8]) -> bool {
+	let is_gl_nodes = name
+		.get(..3)
+		.is_some_and(|prefix| prefix.eq_ignore_ascii_case(b"GL_"));
+
+	// GL nodes lumps are named GL_VERT, GL_SEGS, etc.
+	MAP_LUMP_NAMES
+		.iter()
+		.any(|map_lump| map_lump.eq_ignore_ascii_case(name))
+		|| is_gl_nodes
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::test_util::name;
+	use crate::{Wad, WadBuilder, WadString, WadType};
+
+	#[test]
+	fn lumps_iterate_with_handles() {
+		let mut builder = WadBuilder::new(WadType::PWAD);
+		builder
+			.add_marker(name("E1M1"))
+			.add_lump(name("THINGS"), b"things".to_vec())
+			.add_lump(name("LINEDEFS"), b"linedefs".to_vec())
+			.add_marker(name("E1M2"))
+			.add_lump(WadString::new(*b"things\0\0").unwrap(), b"things2".to_vec())
+			.add_lump(name("DEMO1"), b"demo".to_vec());
+		let wad = Wad::from_bytes(builder.to_bytes().unwrap()).unwrap();
+
+		assert_eq!(wad.lumps().len(), 6);
+		let last = wad.lumps().next_back().unwrap();
+		assert_eq!(last.index(), 5);
+		assert_eq!(last.read().unwrap(), b"demo");
+
+		let e1m1 = wad
+			.map_lumps("E1M1")
+			.unwrap()
+			.map(|lump| lump.name().to_string())
+			.collect::<Vec<_>>();
+		assert_eq!(e1m1, ["THINGS", "LINEDEFS"]);
+
+		let e1m2 = wad.map_lumps("E1M2").unwrap().collect::<Vec<_>>();
+		assert_eq!(e1m2.len(), 1);
+		assert_eq!(e1m2[0].read().unwrap(), b"things2");
+
+		assert!(wad.map_lumps("MAP01").is_none());
+		assert!(wad.lump(6).is_none());
+		assert_eq!(wad.lump_named("DEMO1").unwrap().size_bytes(), 4);
+	}
+}
@@ -0,0 +1,345 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Write as _};
+
+use crate::{Wad, WadBuilder, WadError, WadSource, WadString};
+
+/// Descriptive text lumps that WADs conventionally carry: the free-form
+/// `WADINFO` and `CREDITS` lumps, and `RDINFO`, a small TOML lump with
+/// structured authorship and licensing information.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WadMetadata {
+	/// Contents of the `WADINFO` lump
+	pub wadinfo: Option<String>,
+	/// Contents of the `CREDITS` lump
+	pub credits: Option<String>,
+	/// Parsed contents of the `RDINFO` lump, or where parsing it failed
+	pub info: Option<Result<RdInfo, RdInfoError>>,
+}
+
+/// The structured metadata in an `RDINFO` lump. It uses a subset of TOML:
+/// top-level keys with string values, and `#` comments. `authors` is the only
+/// key that takes an array of strings.
+///
+/// ```toml
+/// title = "Example Megawad"
+/// authors = ["Jane Doe"]
+/// license = "CC-BY-4.0"
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RdInfo {
+	pub title: Option<String>,
+	pub authors: Vec<String>,
+	pub license: Option<String>,
+	pub version: Option<String>,
+	pub description: Option<String>,
+	/// Other keys, which must have string values. Keys may only contain ASCII
+	/// letters, digits, `_` and `-`, and can't be one of the keys above.
+	pub extra: BTreeMap<String, String>,
+}
+
+/// An `RDINFO` lump that couldn't be parsed by [`RdInfo::parse`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RdInfoError {
+	/// The 1-based line where parsing failed
+	pub line: usize,
+}
+
+impl Display for RdInfoError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "invalid metadata on line {}", self.line)
+	}
+}
+
+impl std::error::Error for RdInfoError {}
+
+impl From<RdInfoError> for WadError {
+	fn from(err: RdInfoError) -> Self {
+		WadError::InvalidMetadata { line: err.line }
+	}
+}
+
+impl RdInfo {
+	pub fn parse(text: &str) -> Result<Self, RdInfoError> {
+		let mut info = RdInfo::default();
+
+		for (line_index, line) in text.lines().enumerate() {
+			let invalid = || RdInfoError {
+				line: line_index + 1,
+			};
+
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+
+			let (key, value) = line.split_once('=').ok_or_else(invalid)?;
+			let key = key.trim();
+			let value = value.trim();
+
+			if key == "authors" {
+				let items = value
+					.strip_prefix('[')
+					.and_then(|value| value.strip_suffix(']'))
+					.ok_or_else(invalid)?;
+
+				info.authors = split_array(items)
+					.map(|item| parse_string(item).ok_or_else(invalid))
+					.collect::<Result<_, _>>()?;
+				continue;
+			}
+
+			let value = parse_string(value).ok_or_else(invalid)?;
+			match key {
+				"title" => info.title = Some(value),
+				"license" => info.license = Some(value),
+				"version" => info.version = Some(value),
+				"description" => info.description = Some(value),
+				_ => {
+					info.extra.insert(key.to_owned(), value);
+				}
+			}
+		}
+
+		Ok(info)
+	}
+
+	/// Write the metadata as TOML. Fails if a key in `extra` isn't valid.
+	pub fn to_toml(&self) -> Result<String, WadError> {
+		if let Some(key) = self.extra.keys().find(|key| !is_extra_key(key)) {
+			return Err(WadError::InvalidMetadataKey(key.clone()));
+		}
+
+		let mut text = String::new();
+		let fields = [
+			("title", &self.title),
+			("license", &self.license),
+			("version", &self.version),
+			("description", &self.description),
+		];
+
+		for (key, value) in fields {
+			if let Some(value) = value {
+				writeln!(text, "{} = {}", key, quote(value)).unwrap();
+			}
+		}
+		if !self.authors.is_empty() {
+			let authors = self.authors.iter().map(|author| quote(author));
+			writeln!(
+				text,
+				"authors = [{}]",
+				authors.collect::<Vec<_>>().join(", ")
+			)
+			.unwrap();
+		}
+		for (key, value) in &self.extra {
+			writeln!(text, "{} = {}", key, quote(value)).unwrap();
+		}
+
+		Ok(text)
+	}
+}
+
+impl<S: WadSource> Wad<S> {
+	/// Read the WAD's metadata lumps. Lumps that aren't present are `None`. An
+	/// `RDINFO` lump that can't be parsed doesn't stop the other lumps from being
+	/// read; its error is kept in [`WadMetadata::info`].
+	pub fn metadata(&self) -> Result<WadMetadata, WadError> {
+		let read_text = |name: &str| -> Result<Option<String>, WadError> {
+			match self
+				.index_of(name)
+				.and_then(|index| self.directory.get(index))
+			{
+				Some(entry) => {
+					let data = self.read_lump(entry)?;
+					Ok(Some(String::from_utf8_lossy(&data).into_owned()))
+				}
+				None => Ok(None),
+			}
+		};
+
+		Ok(WadMetadata {
+			wadinfo: read_text("WADINFO")?,
+			credits: read_text("CREDITS")?,
+			info: read_text("RDINFO")?.map(|text| RdInfo::parse(&text)),
+		})
+	}
+}
+
+impl WadBuilder {
+	/// Add lumps for each piece of metadata that is present. `RDINFO` metadata
+	/// that failed to parse is skipped. Fails without adding anything if the
+	/// `RDINFO` metadata can't be written.
+	pub fn add_metadata(&mut self, metadata: &WadMetadata) -> Result<&mut Self, WadError> {
+		let info = match &metadata.info {
+			Some(Ok(info)) => Some(info.to_toml()?),
+			_ => None,
+		};
+
+		if let Some(wadinfo) = &metadata.wadinfo {
+			self.add_lump(WadString::new(*b"WADINFO\0").unwrap(), wadinfo.as_bytes());
+		}
+		if let Some(credits) = &metadata.credits {
+			self.add_lump(WadString::new(*b"CREDITS\0").unwrap(), credits.as_bytes());
+		}
+		if let Some(info) = info {
+			self.add_lump(WadString::new(*b"RDINFO\0\0").unwrap(), info);
+		}
+		Ok(self)
+	}
+}
+
+/// Whether `key` can be used in [`RdInfo::extra`]: a bare TOML key that isn't
+/// one of the standard keys
+fn is_extra_key(key: &str) -> bool {
+	let standard = ["title", "authors", "license", "version", "description"];
+
+	!key.is_empty()
+		&& key
+			.chars()
+			.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+		&& !standard.contains(&key)
+}
+
+/// Split the items of an array on commas outside of strings
+fn split_array(items: &str) -> impl Iterator<Item = &str> {
+	let mut in_string = false;
+	let mut escaped = false;
+	let mut start = 0;
+	let mut parts = Vec::new();
+
+	for (i, c) in items.char_indices() {
+		match c {
+			_ if escaped => escaped = false,
+			'\\' if in_string => escaped = true,
+			'"' => in_string = !in_string,
+			',' if !in_string => {
+				parts.push(&items[start..i]);
+				start = i + 1;
+			}
+			_ => {}
+		}
+	}
+	parts.push(&items[start..]);
+
+	parts
+		.into_iter()
+		.map(str::trim)
+		.filter(|part| !part.is_empty())
+}
+
+/// Parse a double-quoted TOML basic string
+fn parse_string(value: &str) -> Option<String> {
+	let inner = value.strip_prefix('"')?.strip_suffix('"')?;
+
+	let mut string = String::with_capacity(inner.len());
+	let mut chars = inner.chars();
+	while let Some(c) = chars.next() {
+		match c {
+			'\\' => string.push(match chars.next()? {
+				'"' => '"',
+				'\\' => '\\',
+				'n' => '\n',
+				't' => '\t',
+				_ => return None,
+			}),
+			'"' => return None,
+			_ => string.push(c),
+		}
+	}
+
+	Some(string)
+}
+
+fn quote(value: &str) -> String {
+	let mut quoted = String::from('"');
+	for c in value.chars() {
+		match c {
+			'"' => quoted.push_str("\\\""),
+			'\\' => quoted.push_str("\\\\"),
+			'\n' => quoted.push_str("\\n"),
+			'\t' => quoted.push_str("\\t"),
+			_ => quoted.push(c),
+		}
+	}
+	quoted.push('"');
+	quoted
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::WadType;
+
+	#[test]
+	fn rdinfo_parse() {
+		let info = RdInfo::parse(
+			"# Release info\n\
+			 title = \"Example \\\"3\\\"\"\n\
+			 authors = [\"Jane Doe\", \"A, B\"]\n\
+			 license = \"CC-BY-4.0\"\n\
+			 homepage = \"https://example.com\"\n",
+		)
+		.unwrap();
+
+		assert_eq!(info.title.as_deref(), Some("Example \"3\""));
+		assert_eq!(info.authors, ["Jane Doe", "A, B"]);
+		assert_eq!(info.license.as_deref(), Some("CC-BY-4.0"));
+		assert_eq!(info.extra["homepage"], "https://example.com");
+
+		let result = RdInfo::parse("tags = [\"a\"]\n");
+		assert_eq!(result, Err(RdInfoError { line: 1 }));
+
+		let result = RdInfo::parse("title = \"ok\"\nlicense = unquoted\n");
+		assert_eq!(result, Err(RdInfoError { line: 2 }));
+	}
+
+	#[test]
+	fn metadata_round_trip() {
+		let metadata = WadMetadata {
+			wadinfo: Some("A megawad".to_owned()),
+			credits: None,
+			info: Some(Ok(RdInfo {
+				title: Some("Test".to_owned()),
+				authors: vec!["Someone".to_owned()],
+				version: Some("1.0".to_owned()),
+				..RdInfo::default()
+			})),
+		};
+
+		let mut builder = WadBuilder::new(WadType::PWAD);
+		builder.add_metadata(&metadata).unwrap();
+		let wad = Wad::from_bytes(builder.to_bytes().unwrap()).unwrap();
+
+		assert_eq!(wad.metadata().unwrap(), metadata);
+	}
+
+	#[test]
+	fn metadata_keeps_other_lumps_when_rdinfo_is_invalid() {
+		let mut builder = WadBuilder::new(WadType::PWAD);
+		builder
+			.add_lump(WadString::new(*b"WADINFO\0").unwrap(), "A megawad")
+			.add_lump(WadString::new(*b"RDINFO\0\0").unwrap(), "title = oops");
+		let wad = Wad::from_bytes(builder.to_bytes().unwrap()).unwrap();
+
+		let metadata = wad.metadata().unwrap();
+		assert_eq!(metadata.wadinfo.as_deref(), Some("A megawad"));
+		assert_eq!(metadata.info, Some(Err(RdInfoError { line: 1 })));
+	}
+
+	#[test]
+	fn rdinfo_rejects_invalid_extra_keys() {
+		for key in ["authors", "title", "a = b", "two words", ""] {
+			let mut info = RdInfo::default();
+			info.extra.insert(key.to_owned(), "value".to_owned());
+
+			let result = info.to_toml();
+			assert!(matches!(result, Err(WadError::InvalidMetadataKey(_))));
+		}
+
+		let mut info = RdInfo::default();
+		info.extra
+			.insert("home-page_2".to_owned(), "value".to_owned());
+		let text = info.to_toml().unwrap();
+		assert_eq!(RdInfo::parse(&text).unwrap(), info);
+	}
+}
@@ -0,0 +1,43 @@
+use std::collections::HashMap;
+
+use crate::{WadDirectoryEntry, WadString};
+
+/// Maps lump names to the indices of the directory entries with that name.
+/// Names are case-insensitive, as in vanilla.
+#[derive(Debug, Default)]
+pub(crate) struct NameIndex {
+	indices: HashMap<[u8; WadString::SIZE_BYTES], Vec<usize>>,
+}
+
+impl NameIndex {
+	pub fn new(directory: &[WadDirectoryEntry]) -> Self {
+		let mut indices: HashMap<_, Vec<usize>> = HashMap::with_capacity(directory.len());
+		for (index, entry) in directory.iter().enumerate() {
+			if let Some(key) = key(entry.lump_name.trimmed_bytes()) {
+				indices.entry(key).or_default().push(index);
+			}
+		}
+
+		NameIndex { indices }
+	}
+
+	/// Indices of all entries with the name, in directory order
+	pub fn get(&self, name: &[u8]) -> &[usize] {
+		key(name)
+			.and_then(|key| self.indices.get(&key))
+			.map_or(&[], Vec::as_slice)
+	}
+}
+
+/// Uppercase a name and pad it with nulls, so that names differing only in case
+/// or in garbage after their terminating null are the same key
+fn key(name: &[u8]) -> Option<[u8; WadString::SIZE_BYTES]> {
+	if name.len() > WadString::SIZE_BYTES {
+		return None;
+	}
+
+	let mut key = [0; WadString::SIZE_BYTES];
+	key[..name.len()].copy_from_slice(name);
+	key.make_ascii_uppercase();
+	Some(key)
+}
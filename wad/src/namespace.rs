@@ -0,0 +1,167 @@
+use crate::WadDirectoryEntry;
+
+/// The namespace a lump belongs to, determined by the marker lumps surrounding
+/// it in the directory (e.g. sprites are found between `S_START` and `S_END`).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Namespace {
+	/// Not between any pair of markers. Marker lumps are also in this namespace.
+	Global,
+	Sprites,
+	Flats,
+	Patches,
+}
+
+impl Namespace {
+	/// The namespace started by a marker lump with this name, if any. The doubled
+	/// `SS_START` style markers are a convention used by PWADs to add to the
+	/// IWAD's namespaces.
+	fn started_by(name: &[u8]) -> Option<Self> {
+		match name {
+			b"S_START" | b"SS_START" => Some(Self::Sprites),
+			b"F_START" | b"FF_START" => Some(Self::Flats),
+			b"P_START" | b"PP_START" => Some(Self::Patches),
+			_ => None,
+		}
+	}
+
+	/// The namespace ended by a marker lump with this name, if any
+	fn ended_by(name: &[u8]) -> Option<Self> {
+		match name {
+			b"S_END" | b"SS_END" => Some(Self::Sprites),
+			b"F_END" | b"FF_END" => Some(Self::Flats),
+			b"P_END" | b"PP_END" => Some(Self::Patches),
+			_ => None,
+		}
+	}
+
+	/// Markers that subdivide a namespace without starting or ending one, such as
+	/// `F1_START` and `P2_END` in the IWADs
+	fn is_inner_marker(name: &[u8]) -> bool {
+		match name {
+			[b'F' | b'P', b'1'..=b'9', b'_', rest @ ..] => rest == b"START" || rest == b"END",
+			_ => false,
+		}
+	}
+}
+
+/// Determine the namespace of each entry in a directory. The returned `Vec` is
+/// parallel to `directory`. Marker names are matched ignoring case.
+pub fn namespaces(directory: &[WadDirectoryEntry]) -> Vec<Namespace> {
+	let mut current = Namespace::Global;
+
+	directory
+		.iter()
+		.map(|entry| {
+			let name = entry.lump_name.to_uppercase();
+			let name = name.trimmed_bytes();
+
+			if let Some(namespace) = Namespace::started_by(name) {
+				current = namespace;
+				Namespace::Global
+			} else if Namespace::is_inner_marker(name) {
+				Namespace::Global
+			} else if Namespace::ended_by(name) == Some(current) {
+				current = Namespace::Global;
+				Namespace::Global
+			} else {
+				current
+			}
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::WadString;
+
+	fn entry(name: &[u8]) -> WadDirectoryEntry {
+		let mut bytes = [0; WadString::SIZE_BYTES];
+		bytes[..name.len()].copy_from_slice(name);
+
+		WadDirectoryEntry {
+			offset_bytes: 0,
+			size_bytes: 0,
+			lump_name: WadString::new(bytes).unwrap(),
+		}
+	}
+
+	#[test]
+	fn namespaces_follow_markers() {
+		let directory = [
+			entry(b"PLAYPAL"),
+			entry(b"S_START"),
+			entry(b"TROOA1"),
+			entry(b"S_END"),
+			entry(b"F_START"),
+			entry(b"FLOOR4_8"),
+			entry(b"F_END"),
+			entry(b"E1M1"),
+		];
+
+		assert_eq!(
+			namespaces(&directory),
+			[
+				Namespace::Global,
+				Namespace::Global,
+				Namespace::Sprites,
+				Namespace::Global,
+				Namespace::Global,
+				Namespace::Flats,
+				Namespace::Global,
+				Namespace::Global,
+			]
+		);
+	}
+
+	#[test]
+	fn namespaces_handle_pwad_markers() {
+		let directory = [
+			entry(b"FF_START"),
+			entry(b"F1_START"),
+			entry(b"NUKAGE1"),
+			entry(b"F1_END"),
+			entry(b"F_END"),
+			entry(b"SS_START"),
+			entry(b"PLAYA1"),
+			entry(b"S_END"),
+			entry(b"DSPISTOL"),
+		];
+
+		assert_eq!(
+			namespaces(&directory),
+			[
+				Namespace::Global,
+				Namespace::Global,
+				Namespace::Flats,
+				Namespace::Global,
+				Namespace::Global,
+				Namespace::Global,
+				Namespace::Sprites,
+				Namespace::Global,
+				Namespace::Global,
+			]
+		);
+	}
+
+	#[test]
+	fn namespace_markers_ignore_case() {
+		let directory = [
+			entry(b"f_start"),
+			entry(b"floor4_8"),
+			entry(b"F_end"),
+			entry(b"e1m1"),
+		];
+
+		assert_eq!(
+			namespaces(&directory),
+			[
+				Namespace::Global,
+				Namespace::Flats,
+				Namespace::Global,
+				Namespace::Global,
+			]
+		);
+	}
+}
@@ -0,0 +1,96 @@
+use std::fs::File;
+use std::path::Path;
+
+use crate::{Wad, WadError, WadSource};
+
+/// Options controlling how a [`Wad`] is loaded. Create with [`Wad::options`].
+#[derive(Clone, Debug, Default)]
+pub struct WadOptions {
+	pub(crate) lenient: bool,
+	pub(crate) limits: WadLimits,
+	pub(crate) cache_bytes: usize,
+	pub(crate) writable: bool,
+}
+
+impl WadOptions {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// In lenient mode, lump names with non-ASCII characters are kept as-is (see
+	/// [`crate::WadString::new_lossy`]) instead of failing the load.
+	///
+	/// Lumps with a negative offset or size also fail the load in strict mode
+	/// (the default), with [`WadError::InvalidLumpExtent`]. In lenient mode they
+	/// are kept, with a [`crate::WadWarning::InvalidLumpExtent`] warning, and
+	/// reading them returns an error.
+	pub fn lenient(&mut self, lenient: bool) -> &mut Self {
+		self.lenient = lenient;
+		self
+	}
+
+	/// Strict loading with [`WadLimits::untrusted`], for WADs from untrusted
+	/// sources. Check [`Wad::verdict`] after loading for problems that don't
+	/// prevent loading.
+	pub fn untrusted(&mut self) -> &mut Self {
+		self.lenient = false;
+		self.limits = WadLimits::untrusted();
+		self
+	}
+
+	/// Limits that the WAD's directory must stay within to be loaded
+	pub fn limits(&mut self, limits: WadLimits) -> &mut Self {
+		self.limits = limits;
+		self
+	}
+
+	/// Keep up to `budget_bytes` of recently read lumps in memory for
+	/// [`Wad::cached_lump`]. The default is 0, which disables the cache.
+	pub fn cache(&mut self, budget_bytes: usize) -> &mut Self {
+		self.cache_bytes = budget_bytes;
+		self
+	}
+
+	/// Open the file for writing as well as reading in [`WadOptions::open`], as
+	/// needed by [`Wad::patch_lump_bytes`] and [`crate::WadEditor`]. The default
+	/// is read-only.
+	pub fn writable(&mut self, writable: bool) -> &mut Self {
+		self.writable = writable;
+		self
+	}
+
+	/// Open and load the WAD file at `path`
+	pub fn open<P: AsRef<Path>>(&self, path: P) -> Result<Wad, WadError> {
+		let file = File::options().read(true).write(self.writable).open(path)?;
+		self.load(file)
+	}
+
+	/// Load a WAD from an already opened file or other source
+	pub fn load<S: WadSource>(&self, source: S) -> Result<Wad<S>, WadError> {
+		Wad::load(source, self)
+	}
+}
+
+/// Upper bounds on the size of a WAD's directory and lumps. `None` means no
+/// limit.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct WadLimits {
+	/// Maximum number of entries in the directory
+	pub max_lumps: Option<usize>,
+	/// Maximum size of any single lump
+	pub max_lump_bytes: Option<usize>,
+	/// Maximum total size of all lumps
+	pub max_total_lump_bytes: Option<u64>,
+}
+
+impl WadLimits {
+	/// Conservative limits for loading WADs from untrusted sources. These are
+	/// well above what real-world WADs need, but keep memory use bounded.
+	pub fn untrusted() -> Self {
+		WadLimits {
+			max_lumps: Some(65_536),
+			max_lump_bytes: Some(64 * 1024 * 1024),
+			max_total_lump_bytes: Some(512 * 1024 * 1024),
+		}
+	}
+}
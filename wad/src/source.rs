@@ -0,0 +1,175 @@
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::io::SeekFrom;
+use std::sync::Mutex;
+
+/// Something WAD data can be read from, at any offset. Reads don't depend on
+/// any shared position, so sources that are `Sync` can be read from several
+/// threads at once.
+pub trait WadSource {
+	/// Read exactly enough bytes to fill `buf`, starting at `offset`
+	fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()>;
+
+	/// Total size of the data
+	fn size_bytes(&self) -> io::Result<u64>;
+}
+
+/// Files are read with positional reads, which don't move the file's cursor.
+impl WadSource for File {
+	fn size_bytes(&self) -> io::Result<u64> {
+		Ok(self.metadata()?.len())
+	}
+
+	#[cfg(unix)]
+	fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+		std::os::unix::fs::FileExt::read_exact_at(self, buf, offset)
+	}
+
+	#[cfg(windows)]
+	fn read_exact_at(&self, mut buf: &mut [u8], mut offset: u64) -> io::Result<()> {
+		use std::os::windows::fs::FileExt;
+
+		while !buf.is_empty() {
+			match self.seek_read(buf, offset) {
+				Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+				Ok(n) => {
+					buf = &mut buf[n..];
+					offset += n as u64;
+				}
+				Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+				Err(err) => return Err(err),
+			}
+		}
+		Ok(())
+	}
+
+	#[cfg(not(any(unix, windows)))]
+	fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+		let mut file = self;
+
+		file.seek(SeekFrom::Start(offset))?;
+		file.read_exact(buf)
+	}
+}
+
+/// Write all of `buf` to a file starting at `offset`, without moving the file's
+/// cursor where the platform allows it
+pub(crate) fn write_all_at(file: &File, buf: &[u8], offset: u64) -> io::Result<()> {
+	#[cfg(unix)]
+	{
+		std::os::unix::fs::FileExt::write_all_at(file, buf, offset)
+	}
+
+	#[cfg(windows)]
+	{
+		use std::os::windows::fs::FileExt;
+
+		let (mut buf, mut offset) = (buf, offset);
+		while !buf.is_empty() {
+			match file.seek_write(buf, offset) {
+				Ok(0) => return Err(io::Error::from(io::ErrorKind::WriteZero)),
+				Ok(n) => {
+					buf = &buf[n..];
+					offset += n as u64;
+				}
+				Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+				Err(err) => return Err(err),
+			}
+		}
+		Ok(())
+	}
+
+	#[cfg(not(any(unix, windows)))]
+	{
+		let mut file = file;
+
+		file.seek(SeekFrom::Start(offset))?;
+		file.write_all(buf)
+	}
+}
+
+/// Adapts any `Read + Seek` reader (a network stream, an archive entry, a
+/// `Cursor`, ...) into a [`WadSource`]. The reader is behind a lock, so reads
+/// from several threads are safe but happen one at a time.
+#[derive(Debug)]
+pub struct ReaderSource<R> {
+	reader: Mutex<R>,
+}
+
+impl<R: Read + Seek> ReaderSource<R> {
+	pub fn new(reader: R) -> Self {
+		ReaderSource {
+			reader: Mutex::new(reader),
+		}
+	}
+
+	pub fn into_inner(self) -> R {
+		self.reader
+			.into_inner()
+			.unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+}
+
+impl<R: Read + Seek> WadSource for ReaderSource<R> {
+	fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+		// A panic mid-read can't leave the reader in a state that matters, since
+		// every read seeks first
+		let mut reader = self
+			.reader
+			.lock()
+			.unwrap_or_else(|poisoned| poisoned.into_inner());
+
+		reader.seek(SeekFrom::Start(offset))?;
+		reader.read_exact(buf)
+	}
+
+	fn size_bytes(&self) -> io::Result<u64> {
+		let mut reader = self
+			.reader
+			.lock()
+			.unwrap_or_else(|poisoned| poisoned.into_inner());
+
+		reader.seek(SeekFrom::End(0))
+	}
+}
+
+/// WAD data held in memory, such as a `Vec<u8>` or the `&'static [u8]` from
+/// `include_bytes!`
+#[derive(Debug)]
+pub struct MemorySource<B> {
+	bytes: B,
+}
+
+impl<B: AsRef<[u8]>> MemorySource<B> {
+	pub fn new(bytes: B) -> Self {
+		MemorySource { bytes }
+	}
+
+	pub fn into_inner(self) -> B {
+		self.bytes
+	}
+
+	pub fn as_bytes(&self) -> &[u8] {
+		self.bytes.as_ref()
+	}
+}
+
+impl<B: AsRef<[u8]>> WadSource for MemorySource<B> {
+	fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+		let bytes = self.bytes.as_ref();
+
+		let start = usize::try_from(offset).unwrap_or(usize::MAX);
+		let data = start
+			.checked_add(buf.len())
+			.and_then(|end| bytes.get(start..end))
+			.ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+
+		buf.copy_from_slice(data);
+		Ok(())
+	}
+
+	fn size_bytes(&self) -> io::Result<u64> {
+		Ok(self.bytes.as_ref().len() as u64)
+	}
+}
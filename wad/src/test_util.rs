@@ -0,0 +1,33 @@
+use std::fs::File;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::WadString;
+
+/// A lump name, uppercased as by [`WadString::try_from`]
+pub(crate) fn name(name: &str) -> WadString {
+	WadString::try_from(name).unwrap()
+}
+
+/// Write bytes to a new temporary file, returning its path. The caller is
+/// responsible for removing it.
+pub(crate) fn temp_path(bytes: &[u8]) -> PathBuf {
+	static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+	let path = std::env::temp_dir().join(format!(
+		"rdoom-wad-{}-{}.wad",
+		std::process::id(),
+		NEXT_ID.fetch_add(1, Ordering::Relaxed)
+	));
+	std::fs::write(&path, bytes).unwrap();
+	path
+}
+
+/// Write bytes to a temporary file and open it for reading and writing. The
+/// file is unlinked straight away, so it's cleaned up when closed.
+pub(crate) fn temp_file(bytes: &[u8]) -> File {
+	let path = temp_path(bytes);
+	let file = File::options().read(true).write(true).open(&path).unwrap();
+	std::fs::remove_file(&path).ok();
+	file
+}
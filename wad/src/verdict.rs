@@ -0,0 +1,86 @@
+use std::io;
+
+use crate::{Wad, WadSource, WadWarning};
+
+/// A summary of whether a loaded WAD is complete and consistent, from
+/// [`Wad::verdict`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WadVerdict {
+	/// Indices of lumps whose data extends past the end of the WAD
+	pub out_of_bounds_lumps: Vec<usize>,
+	/// Warnings from loading the WAD
+	pub warnings: Vec<WadWarning>,
+}
+
+impl WadVerdict {
+	/// Whether no problems were found, so every lump can be read
+	pub fn is_ok(&self) -> bool {
+		self.out_of_bounds_lumps.is_empty() && self.warnings.is_empty()
+	}
+}
+
+impl<S: WadSource> Wad<S> {
+	/// Check that the WAD is complete and consistent, without reading any lumps
+	pub fn verdict(&self) -> io::Result<WadVerdict> {
+		let size_bytes = self.source.size_bytes()?;
+
+		let out_of_bounds_lumps = self
+			.directory
+			.iter()
+			.enumerate()
+			.filter(|(_, entry)| !entry.is_virtual())
+			.filter(|(_, entry)| {
+				let end = entry.offset_bytes as i64 + entry.size_bytes as i64;
+				end > size_bytes as i64
+			})
+			.map(|(index, _)| index)
+			.collect();
+
+		Ok(WadVerdict {
+			out_of_bounds_lumps,
+			warnings: self.warnings().to_vec(),
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{MemorySource, Wad, WadError, WadLimits};
+
+	#[test]
+	fn verdict_finds_out_of_bounds_lumps() {
+		let mut bytes = Vec::new();
+		bytes.extend_from_slice(b"PWAD\x02\0\0\0\x0C\0\0\0");
+		bytes.extend_from_slice(b"\x2C\0\0\0\x04\0\0\0GOOD\0\0\0\0");
+		bytes.extend_from_slice(b"\x2C\0\0\0\x40\0\0\0BAD\0\0\0\0\0");
+		bytes.extend_from_slice(b"data");
+
+		let wad = Wad::options()
+			.untrusted()
+			.load(MemorySource::new(&bytes[..]))
+			.unwrap();
+		let verdict = wad.verdict().unwrap();
+		assert_eq!(verdict.out_of_bounds_lumps, [1]);
+		assert!(!verdict.is_ok());
+	}
+
+	#[test]
+	fn untrusted_limits_reject_oversized_wads() {
+		let mut bytes = Vec::new();
+		bytes.extend_from_slice(b"PWAD\x01\0\0\0\x0C\0\0\0");
+		bytes.extend_from_slice(b"\x0C\0\0\0\0\0\0\x7FHUGE\0\0\0\0");
+
+		let result = Wad::options()
+			.limits(WadLimits::untrusted())
+			.load(MemorySource::new(&bytes[..]));
+		assert!(matches!(
+			result,
+			Err(WadError::LumpTooLarge { index: 0, .. })
+		));
+
+		// A header claiming far more lumps than the file holds doesn't allocate
+		let bytes = b"PWAD\xFF\xFF\xFF\x7F\x0C\0\0\0";
+		let result = Wad::from_bytes(&bytes[..]);
+		assert!(matches!(result, Err(WadError::TruncatedDirectory)));
+	}
+}
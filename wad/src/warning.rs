@@ -0,0 +1,40 @@
+use std::fmt::Display;
+
+use crate::WadString;
+
+/// Something wrong with a WAD that didn't stop it from loading. Collected while
+/// loading and available from [`crate::Wad::warnings`], for tools and consoles
+/// to report however they like.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum WadWarning {
+	/// A lump name has non-ASCII characters, and was kept as-is because the WAD
+	/// was loaded in lenient mode
+	NonAsciiLumpName { index: usize, lump_name: WadString },
+	/// A lump has a negative offset or size, so its data can't be read. Only
+	/// possible in lenient mode; otherwise the WAD fails to load.
+	InvalidLumpExtent {
+		index: usize,
+		offset_bytes: i32,
+		size_bytes: i32,
+	},
+}
+
+impl Display for WadWarning {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::NonAsciiLumpName { index, lump_name } => {
+				write!(f, "lump {} has a non-ASCII name: {}", index, lump_name)
+			}
+			Self::InvalidLumpExtent {
+				index,
+				offset_bytes,
+				size_bytes,
+			} => write!(
+				f,
+				"lump {} has an invalid offset ({}) or size ({})",
+				index, offset_bytes, size_bytes
+			),
+		}
+	}
+}